@@ -0,0 +1,263 @@
+use crate::{
+    chains::ChainSummary, errors::ServerError, export::*, validators::*, AppState,
+    TransactionFormInput,
+};
+use actix_example_service::{
+    sea_orm::DbErr, Mutation, Query, TransactionFilter, TransactionSort, TransactionWithFees,
+};
+use actix_web::{
+    error, get,
+    http::{header, StatusCode},
+    post, web, HttpResponse,
+};
+use derive_more::Display;
+use entity::crawl_jobs;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+const MAX_LISTED_CRAWLS: u64 = 100;
+
+/// Server error, rendered as JSON `{"error": "..."}`
+#[derive(Debug, Display)]
+pub struct ApiError(ServerError);
+
+impl From<ServerError> for ApiError {
+    fn from(server_error: ServerError) -> Self {
+        Self(server_error)
+    }
+}
+
+impl From<DbErr> for ApiError {
+    fn from(db_err: DbErr) -> Self {
+        Self(ServerError::from(db_err))
+    }
+}
+
+impl error::ResponseError for ApiError {
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(json!({ "error": self.0.to_string() }))
+    }
+
+    fn status_code(&self) -> StatusCode {
+        self.0.status_code()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PageParams {
+    page: Option<u64>,
+    per_page: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    #[serde(default)]
+    format: ExportFormat,
+    #[serde(default)]
+    units: Units,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CrawlsParams {
+    chain: Option<String>,
+    address: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TransactionsPage {
+    transactions: Vec<TransactionWithFees>,
+    page: u64,
+    per_page: u64,
+    num_pages: u64,
+    total: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct ChainsList {
+    chains: Vec<ChainSummary>,
+}
+
+#[derive(Debug, Serialize)]
+struct Crawls {
+    crawls: Vec<crawl_jobs::Model>,
+}
+
+/// Configured chains, their names or ids are accepted wherever chain is expected
+#[get("/chains")]
+async fn list_chains(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    Ok(HttpResponse::Ok().json(ChainsList {
+        chains: data.chains.summaries(),
+    }))
+}
+
+/// Transactions of address on chain (name or id), filtered by `from_block`, `to_block`, `from_date`, `to_date`,
+/// `direction` (in, out, self), `min_value`, `max_value`, `counterparty`, `min_fee` and
+/// `status` (succeeded, failed). Amounts are in wei. Sorted by `sort` (block, time, value, fee)
+/// in `order` (asc, desc), chain order by default.
+#[get("/chains/{chain}/addresses/{address}/transactions")]
+async fn get_address_transactions(
+    data: web::Data<AppState>,
+    chain_and_address: web::Path<(String, String)>,
+    page_params: web::Query<PageParams>,
+    filter: web::Query<TransactionFilter>,
+    sort: web::Query<TransactionSort>,
+) -> Result<HttpResponse, ApiError> {
+    let (chain, address) = chain_and_address.into_inner();
+    let chain = data.chains.find(&chain)?;
+    let address = parse_eth_address(&address)?;
+
+    let page = page_params.page.unwrap_or(1).max(1);
+    let per_page = data.pages.api_per_page(page_params.per_page);
+    ensure_valid_page("page", page, per_page)?;
+    let filter = normalize_filter(filter.into_inner())?;
+
+    let (transactions, total, num_pages) = Query::find_filtered_transactions_in_page(
+        &data.conn,
+        chain.chain_id,
+        &address,
+        &filter,
+        &sort,
+        page,
+        per_page,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(TransactionsPage {
+        transactions,
+        page,
+        per_page,
+        num_pages,
+        total,
+    }))
+}
+
+/// Streams transactions of address as CSV (`format=csv`, amounts in `units` wei, gwei or eth)
+/// or NDJSON (`format=ndjson`). Takes the same filters as transactions list.
+#[get("/chains/{chain}/addresses/{address}/transactions/export")]
+async fn export_address_transactions(
+    data: web::Data<AppState>,
+    chain_and_address: web::Path<(String, String)>,
+    export_params: web::Query<ExportParams>,
+    filter: web::Query<TransactionFilter>,
+) -> Result<HttpResponse, ApiError> {
+    let (chain, address) = chain_and_address.into_inner();
+    let chain = data.chains.find(&chain)?;
+    let address = parse_eth_address(&address)?;
+    let filter = normalize_filter(filter.into_inner())?;
+
+    let format = export_params.format;
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .append_header((
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"{}_{address}_transactions.{}\"",
+                chain.name,
+                format.extension()
+            ),
+        ))
+        .streaming(export_transactions(
+            data.conn.clone(),
+            chain.chain_id,
+            address,
+            filter,
+            format,
+            export_params.units,
+        )))
+}
+
+#[get("/chains/{chain}/transactions/{hash}")]
+async fn get_transaction(
+    data: web::Data<AppState>,
+    chain_and_hash: web::Path<(String, String)>,
+) -> Result<HttpResponse, ApiError> {
+    let (chain, hash) = chain_and_hash.into_inner();
+    let chain = data.chains.find(&chain)?;
+    let hash = parse_tx_hash(&hash)?;
+
+    let transaction = Query::find_transaction_by_hash(&data.conn, chain.chain_id, &hash)
+        .await?
+        .ok_or(ServerError::TransactionNotFound { hash })?;
+
+    Ok(HttpResponse::Ok().json(transaction))
+}
+
+/// Latest crawls, optionally of given `chain` (name or id) and `address` only
+#[get("/crawls")]
+async fn list_crawls(
+    data: web::Data<AppState>,
+    params: web::Query<CrawlsParams>,
+) -> Result<HttpResponse, ApiError> {
+    let chain_id = params
+        .chain
+        .as_deref()
+        .map(|chain| data.chains.find(chain).map(|chain| chain.chain_id))
+        .transpose()?;
+    let address = params
+        .address
+        .as_deref()
+        .map(parse_eth_address)
+        .transpose()?;
+
+    let crawls =
+        Query::find_latest_crawl_jobs(&data.conn, chain_id, address.as_deref(), MAX_LISTED_CRAWLS)
+            .await?;
+
+    Ok(HttpResponse::Ok().json(Crawls { crawls }))
+}
+
+#[get("/crawls/{id}")]
+async fn get_crawl(
+    data: web::Data<AppState>,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let id = id.into_inner();
+
+    let crawl = Query::find_crawl_job_by_id(&data.conn, id)
+        .await?
+        .ok_or(ServerError::CrawlJobNotFound { id })?;
+
+    Ok(HttpResponse::Ok().json(crawl))
+}
+
+/// Queues crawl of `address` on chain `chain_id` since `starting_block_number`
+/// up to optional `ending_block_number`, progress is available at returned location
+#[post("/crawls")]
+async fn create_crawl(
+    data: web::Data<AppState>,
+    crawl_input: web::Json<TransactionFormInput>,
+) -> Result<HttpResponse, ApiError> {
+    let crawl_input = crawl_input.into_inner();
+
+    let chain = data.chains.get(crawl_input.chain_id)?;
+    let address = parse_eth_address(&crawl_input.address)?;
+    ensure_valid_block_range(
+        crawl_input.starting_block_number,
+        crawl_input.ending_block_number,
+    )?;
+
+    let crawl = Mutation::create_crawl_job(
+        &data.conn,
+        chain.chain_id,
+        address,
+        crawl_input.starting_block_number,
+        crawl_input.ending_block_number,
+    )
+    .await?;
+    data.crawl_jobs.enqueue(crawl.id)?;
+
+    Ok(HttpResponse::Accepted()
+        .append_header((header::LOCATION, format!("/api/v1/crawls/{}", crawl.id)))
+        .json(crawl))
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_chains);
+    cfg.service(get_address_transactions);
+    cfg.service(export_address_transactions);
+    cfg.service(get_transaction);
+    cfg.service(list_crawls);
+    cfg.service(get_crawl);
+    cfg.service(create_crawl);
+}
@@ -0,0 +1,196 @@
+use crate::{
+    config::{Config, ExplorerApiVersion, ResolvedChainConfig},
+    errors::ServerError,
+    eth_api::{EtherscanClient, ETHERSCAN_API},
+    json_rpc::JsonRpcClient,
+    rate_limit::RetryPolicy,
+    transaction_source::{TransactionSource, TransactionSourceKind},
+};
+use serde::Serialize;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+/// Chain, which is crawled if no chains are configured
+pub const MAINNET: KnownChain = KnownChain {
+    name: "mainnet",
+    chain_id: 1,
+    explorer_url: ETHERSCAN_API,
+};
+
+/// Chains, which are configured by name alone
+pub const KNOWN_CHAINS: [KnownChain; 6] = [
+    MAINNET,
+    KnownChain {
+        name: "sepolia",
+        chain_id: 11155111,
+        explorer_url: "https://api-sepolia.etherscan.io/api",
+    },
+    KnownChain {
+        name: "polygon",
+        chain_id: 137,
+        explorer_url: "https://api.polygonscan.com/api",
+    },
+    KnownChain {
+        name: "arbitrum",
+        chain_id: 42161,
+        explorer_url: "https://api.arbiscan.io/api",
+    },
+    KnownChain {
+        name: "optimism",
+        chain_id: 10,
+        explorer_url: "https://api-optimistic.etherscan.io/api",
+    },
+    KnownChain {
+        name: "base",
+        chain_id: 8453,
+        explorer_url: "https://api.basescan.org/api",
+    },
+];
+
+/// Chain id and v1 explorer host of well known chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnownChain {
+    pub name: &'static str,
+    pub chain_id: u64,
+    pub explorer_url: &'static str,
+}
+
+impl KnownChain {
+    pub fn by_name(name: &str) -> Option<&'static Self> {
+        KNOWN_CHAINS.iter().find(|known| known.name == name)
+    }
+}
+
+/// Crawled chain along with its transaction source clients
+#[derive(Debug, Clone)]
+pub struct Chain {
+    pub chain_id: u64,
+    pub name: String,
+    pub source: Arc<dyn TransactionSource>,
+    /// Optional for json rpc source, but internal transactions and token transfers need it
+    pub etherscan: Option<EtherscanClient>,
+    /// Optional for etherscan source, but fee breakdown needs it
+    pub json_rpc: Option<JsonRpcClient>,
+}
+
+/// Name and id of chain, as listed by web app and api
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainSummary {
+    pub chain_id: u64,
+    pub name: String,
+}
+
+/// All configured chains, sorted by name
+#[derive(Debug, Clone)]
+pub struct Chains(Arc<Vec<Chain>>);
+
+impl Chains {
+    /// Builds clients of every configured chain. Chains, which use the same explorer host
+    /// and api key, share one client, so they share its rate limit too.
+    pub fn new(config: &Config) -> Result<Self, ServerError> {
+        let resolved_chains = config.chains().expect("chains are validated on startup");
+
+        let mut etherscan_clients: HashMap<(String, String), EtherscanClient> = HashMap::new();
+        let mut chains = vec![];
+
+        for ResolvedChainConfig {
+            name,
+            chain_id,
+            api_version,
+            api_url,
+            api_key,
+            rpc_url,
+        } in resolved_chains
+        {
+            let etherscan = match api_key {
+                Some(api_key) => {
+                    let client = match etherscan_clients.get(&(api_url.clone(), api_key.clone())) {
+                        Some(client) => client.clone(),
+                        None => {
+                            let client = EtherscanClient::builder(api_key.clone())
+                                .base_url(api_url.clone())
+                                .timeout(Duration::from_secs(config.etherscan.timeout_secs))
+                                .requests_per_second(config.etherscan.requests_per_second)
+                                .retry_policy(RetryPolicy {
+                                    max_retries: config.etherscan.max_retries,
+                                    ..Default::default()
+                                })
+                                .build()?;
+                            etherscan_clients.insert((api_url, api_key), client.clone());
+                            client
+                        }
+                    };
+
+                    // v2 api selects chain by parameter, v1 hosts serve single chain
+                    Some(match api_version {
+                        ExplorerApiVersion::V1 => client,
+                        ExplorerApiVersion::V2 => client.for_chain(chain_id),
+                    })
+                }
+                None => None,
+            };
+
+            let json_rpc = rpc_url
+                .map(|url| {
+                    JsonRpcClient::new(url, Duration::from_secs(config.json_rpc.timeout_secs))
+                })
+                .transpose()?;
+
+            // presence of the client of selected source is checked by config validation
+            let source: Arc<dyn TransactionSource> = match config.crawler.source {
+                TransactionSourceKind::Etherscan => Arc::new(
+                    etherscan
+                        .clone()
+                        .expect("etherscan api key is validated on startup"),
+                ),
+                TransactionSourceKind::JsonRpc => Arc::new(
+                    json_rpc
+                        .clone()
+                        .expect("json rpc url is validated on startup"),
+                ),
+            };
+
+            chains.push(Chain {
+                chain_id,
+                name,
+                source,
+                etherscan,
+                json_rpc,
+            });
+        }
+
+        Ok(Self(Arc::new(chains)))
+    }
+
+    /// Configured chain with given id
+    pub fn get(&self, chain_id: u64) -> Result<&Chain, ServerError> {
+        self.0
+            .iter()
+            .find(|chain| chain.chain_id == chain_id)
+            .ok_or_else(|| ServerError::UnknownChain {
+                chain: chain_id.to_string(),
+            })
+    }
+
+    /// Configured chain with given name or id
+    pub fn find(&self, chain: &str) -> Result<&Chain, ServerError> {
+        self.0
+            .iter()
+            .find(|configured| configured.name == chain || chain.parse() == Ok(configured.chain_id))
+            .ok_or_else(|| ServerError::UnknownChain {
+                chain: chain.to_string(),
+            })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Chain> {
+        self.0.iter()
+    }
+
+    pub fn summaries(&self) -> Vec<ChainSummary> {
+        self.iter()
+            .map(|chain| ChainSummary {
+                chain_id: chain.chain_id,
+                name: chain.name.clone(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,407 @@
+use crate::{
+    config::Config,
+    connect,
+    errors::{ConfigError, ServerError},
+    export::*,
+    jobs::*,
+    serve,
+    transaction_source::TransactionSourceKind,
+    validators::*,
+    Settings,
+};
+use actix_example_service::{
+    sea_orm::{ActiveEnum, DatabaseConnection},
+    Mutation, Query, TransactionFilter,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use entity::crawl_jobs;
+use futures::StreamExt;
+use migration::{Migrator, MigratorTrait};
+use serde_json::json;
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::PathBuf,
+    process::ExitCode,
+    time::Duration,
+};
+
+// How often crawl progress is printed
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Crawls transactions of Ethereum addresses and serves them over HTTP
+#[derive(Debug, Parser)]
+#[command(name = "eth_crawler", version)]
+struct Cli {
+    /// Web server is started if no command is given
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    overrides: ConfigOverrides,
+}
+
+/// Flags, which override config file and environment variables
+#[derive(Debug, Args)]
+struct ConfigOverrides {
+    /// TOML config file, `eth_crawler.toml` is read if it exists and no file is given
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    #[arg(long, global = true)]
+    log_level: Option<String>,
+    #[arg(long, global = true)]
+    host: Option<String>,
+    #[arg(long, global = true)]
+    port: Option<u16>,
+    #[arg(long, global = true)]
+    database_url: Option<String>,
+    #[arg(long, global = true)]
+    database_max_connections: Option<u32>,
+    /// Transaction source: etherscan or json_rpc
+    #[arg(long, global = true, value_parser = parse_source)]
+    source: Option<TransactionSourceKind>,
+    #[arg(long, global = true)]
+    etherscan_api_key: Option<String>,
+    #[arg(long, global = true)]
+    eth_rpc_url: Option<String>,
+    #[arg(long, global = true)]
+    confirmation_depth: Option<u64>,
+}
+
+impl ConfigOverrides {
+    fn apply(self, config: &mut Config) {
+        let Self {
+            config: _,
+            log_level,
+            host,
+            port,
+            database_url,
+            database_max_connections,
+            source,
+            etherscan_api_key,
+            eth_rpc_url,
+            confirmation_depth,
+        } = self;
+
+        if let Some(log_level) = log_level {
+            config.log_level = log_level;
+        }
+        if let Some(host) = host {
+            config.server.host = host;
+        }
+        if let Some(port) = port {
+            config.server.port = port;
+        }
+        if let Some(database_url) = database_url {
+            config.database.url = Some(database_url);
+        }
+        if let Some(max_connections) = database_max_connections {
+            config.database.max_connections = max_connections;
+        }
+        if let Some(source) = source {
+            config.crawler.source = source;
+        }
+        if let Some(api_key) = etherscan_api_key {
+            config.etherscan.api_key = Some(api_key);
+        }
+        if let Some(url) = eth_rpc_url {
+            config.json_rpc.url = Some(url);
+        }
+        if let Some(confirmation_depth) = confirmation_depth {
+            config.crawler.confirmation_depth = confirmation_depth;
+        }
+    }
+}
+
+fn parse_source(value: &str) -> Result<TransactionSourceKind, String> {
+    value.parse()
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Apply pending migrations and start web server along with background crawler
+    Serve,
+    /// Apply pending database migrations
+    Migrate,
+    /// Apply pending migrations, crawl transactions of address into database and wait for crawl to finish
+    Crawl {
+        /// Name or id of configured chain
+        #[arg(long, default_value = "mainnet")]
+        chain: String,
+        #[arg(long)]
+        address: String,
+        #[arg(long)]
+        from_block: u64,
+        /// Current block if not set
+        #[arg(long)]
+        to_block: Option<u64>,
+        /// Format of crawl result, progress is printed to stderr
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Apply pending migrations and export crawled transactions of address
+    Export {
+        /// Name or id of configured chain
+        #[arg(long, default_value = "mainnet")]
+        chain: String,
+        #[arg(long)]
+        address: String,
+        #[arg(long)]
+        from_block: Option<u64>,
+        #[arg(long)]
+        to_block: Option<u64>,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// Units of amounts in CSV export
+        #[arg(long, value_enum, default_value_t = Units::Eth)]
+        units: Units,
+        /// Standard output if not set
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Runs command given on command line, exits with failure on any error
+#[actix_web::main]
+pub async fn run() -> ExitCode {
+    let Cli { command, overrides } = Cli::parse();
+
+    let config = match load_config(overrides) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // log level is validated along with the rest of config
+    tracing_subscriber::fmt()
+        .with_env_filter(config.log_level.as_str())
+        .init();
+
+    let result = match command.unwrap_or(Command::Serve) {
+        Command::Serve => match Settings::new(config) {
+            Ok(settings) => serve(settings).await.map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        },
+        Command::Migrate => migrate(config).await.map_err(|err| err.to_string()),
+        Command::Crawl {
+            chain,
+            address,
+            from_block,
+            to_block,
+            format,
+        } => crawl(config, chain, address, from_block, to_block, format)
+            .await
+            .map_err(|err| err.to_string()),
+        Command::Export {
+            chain,
+            address,
+            from_block,
+            to_block,
+            format,
+            units,
+            output,
+        } => export(
+            config,
+            chain,
+            address,
+            TransactionFilter {
+                from_block,
+                to_block,
+                ..Default::default()
+            },
+            format,
+            units,
+            output,
+        )
+        .await
+        .map_err(|err| err.to_string()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Loads config from file and environment, applies flags and validates the result
+fn load_config(overrides: ConfigOverrides) -> Result<Config, ConfigError> {
+    let mut config = Config::load(overrides.config.as_deref())?;
+    overrides.apply(&mut config);
+    config.validate()?;
+
+    Ok(config)
+}
+
+async fn migrate(config: Config) -> Result<(), ServerError> {
+    let conn = connect(&config.database).await?;
+
+    Migrator::up(&conn, None).await.map_err(ServerError::from)?;
+    println!("Migrations applied");
+
+    Ok(())
+}
+
+/// Runs crawl as a regular crawl job, so it shows up in web app too
+async fn crawl(
+    config: Config,
+    chain: String,
+    address: String,
+    from_block: u64,
+    to_block: Option<u64>,
+    format: OutputFormat,
+) -> Result<(), ServerError> {
+    let address = parse_eth_address(&address)?;
+    ensure_valid_block_range(from_block, to_block)?;
+
+    let settings = Settings::new(config)?;
+    let chain_id = settings.chains.find(&chain)?.chain_id;
+    let conn = connect(&settings.config.database).await?;
+    Migrator::up(&conn, None).await.map_err(ServerError::from)?;
+
+    let job = Mutation::create_crawl_job(&conn, chain_id, address, from_block, to_block)
+        .await
+        .map_err(ServerError::from)?;
+    eprintln!("Crawl job {} started", job.id);
+
+    let progress_printer = actix_web::rt::spawn(print_progress(conn.clone(), job.id));
+
+    let result = run_crawl_job(
+        &conn,
+        &settings.chains,
+        settings.config.crawler.confirmation_depth,
+        settings.config.crawler.reorg_recheck_blocks,
+        job.id,
+    )
+    .await;
+    progress_printer.abort();
+
+    let job = Query::find_crawl_job_by_id(&conn, job.id)
+        .await
+        .map_err(ServerError::from)?
+        .ok_or(ServerError::CrawlJobNotFound { id: job.id })?;
+
+    match format {
+        OutputFormat::Text => println!(
+            "Crawl job {} {}: {} block ranges, {} pages fetched, {} rows saved",
+            job.id,
+            job.status.to_value(),
+            job.ranges_done,
+            job.pages_fetched,
+            job.rows_saved
+        ),
+        OutputFormat::Json => println!("{}", json!(job)),
+    }
+
+    result
+}
+
+/// Prints progress of crawl job to stderr until it's aborted
+async fn print_progress(conn: DatabaseConnection, job_id: i32) {
+    let mut interval = actix_web::rt::time::interval(PROGRESS_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        if let Ok(Some(crawl_jobs::Model {
+            ranges_done,
+            ranges_total,
+            pages_fetched,
+            rows_saved,
+            ..
+        })) = Query::find_crawl_job_by_id(&conn, job_id).await
+        {
+            eprintln!(
+                "{ranges_done}/{ranges_total} block ranges, {pages_fetched} pages fetched, {rows_saved} rows saved"
+            );
+        }
+    }
+}
+
+async fn export(
+    config: Config,
+    chain: String,
+    address: String,
+    filter: TransactionFilter,
+    format: ExportFormat,
+    units: Units,
+    output: Option<PathBuf>,
+) -> Result<(), ServerError> {
+    let address = parse_eth_address(&address)?;
+    let filter = normalize_filter(filter)?;
+
+    let settings = Settings::new(config)?;
+    let chain_id = settings.chains.find(&chain)?.chain_id;
+    let conn = connect(&settings.config.database).await?;
+    Migrator::up(&conn, None).await.map_err(ServerError::from)?;
+
+    let mut writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path).map_err(io_error)?),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    let mut chunks = Box::pin(export_transactions(
+        conn, chain_id, address, filter, format, units,
+    ));
+
+    while let Some(chunk) = chunks.next().await {
+        writer.write_all(&chunk?).map_err(io_error)?;
+    }
+
+    writer.flush().map_err(io_error)
+}
+
+fn io_error(err: io::Error) -> ServerError {
+    ServerError::ExportError {
+        message: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+
+    #[test]
+    fn flags_override_env_and_env_overrides_file() {
+        let path = env::temp_dir().join(format!("eth_crawler_{}_cli.toml", std::process::id()));
+        fs::write(&path, "[crawler]\nconfirmation_depth = 3").unwrap();
+        let load = |flags: &[&str]| {
+            let cli = Cli::try_parse_from(
+                [
+                    "eth_crawler",
+                    "--config",
+                    path.to_str().unwrap(),
+                    "--database-url",
+                    "sqlite::memory:",
+                    "--etherscan-api-key",
+                    "key",
+                ]
+                .iter()
+                .chain(flags),
+            )
+            .unwrap();
+            load_config(cli.overrides)
+        };
+
+        env::set_var("CONFIRMATION_DEPTH", "7");
+        let from_env = load(&[]);
+        let from_flag = load(&["--confirmation-depth", "5"]);
+        env::remove_var("CONFIRMATION_DEPTH");
+        let from_file = load(&[]);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(from_file.unwrap().crawler.confirmation_depth, 3);
+        assert_eq!(from_env.unwrap().crawler.confirmation_depth, 7);
+        assert_eq!(from_flag.unwrap().crawler.confirmation_depth, 5);
+    }
+}
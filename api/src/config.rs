@@ -0,0 +1,786 @@
+use crate::{
+    chains::{KnownChain, MAINNET},
+    crawler::{DEFAULT_CONFIRMATION_DEPTH, DEFAULT_REORG_RECHECK_BLOCKS},
+    errors::ConfigError,
+    eth_api::{DEFAULT_TIMEOUT, ETHERSCAN_V2_API},
+    jobs::DEFAULT_WATCH_INTERVAL,
+    json_rpc::DEFAULT_RPC_TIMEOUT,
+    rate_limit::{DEFAULT_MAX_RETRIES, DEFAULT_REQUESTS_PER_SECOND},
+    transaction_source::TransactionSourceKind,
+};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashSet},
+    env,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use tracing_subscriber::EnvFilter;
+
+/// Config file, which is read from working directory if no other file is given
+pub const DEFAULT_CONFIG_FILE: &str = "eth_crawler.toml";
+
+/// Configuration of the whole app.
+/// Defaults are overridden by config file, then by environment variables and `.env` file,
+/// then by command line flags.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Tracing filter, e.g. `info` or `actix_web=debug,sqlx=warn`
+    pub log_level: String,
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub crawler: CrawlerConfig,
+    pub etherscan: EtherscanConfig,
+    pub json_rpc: JsonRpcConfig,
+    pub pages: PagesConfig,
+    /// Crawled chains by name, mainnet alone is crawled if none are configured
+    pub chains: BTreeMap<String, ChainConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: Option<String>,
+    /// Size of connection pool
+    pub max_connections: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrawlerConfig {
+    pub source: TransactionSourceKind,
+    /// Blocks mined on top of a block before it's crawled
+    pub confirmation_depth: u64,
+    /// Latest blocks re-checked for reorgs, 0 disables re-checks
+    pub reorg_recheck_blocks: u64,
+    /// Interval between scheduled crawls of watched addresses
+    pub watch_interval_secs: u64,
+}
+
+/// Explorer API settings, shared by all chains unless chain overrides them
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EtherscanConfig {
+    /// Required for etherscan source. Optional for json_rpc source,
+    /// but internal transactions and token transfers need it.
+    pub api_key: Option<String>,
+    pub api_version: ExplorerApiVersion,
+    /// Defaults to Etherscan v2 API or to explorer host of known chain for v1 API
+    pub api_url: Option<String>,
+    pub timeout_secs: u64,
+    pub requests_per_second: f64,
+    pub max_retries: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JsonRpcConfig {
+    /// Node of mainnet, unless it sets its own `rpc_url`. Required for json_rpc source.
+    /// Optional for etherscan source, if set EIP-1559/4844 fee breakdown is filled from it.
+    pub url: Option<String>,
+    pub timeout_secs: u64,
+}
+
+/// Flavour of Etherscan-family explorer API
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExplorerApiVersion {
+    /// Explorer on chain's own host, e.g. api.polygonscan.com
+    #[default]
+    V1,
+    /// Etherscan v2 API, single host and api key for all chains, chain is selected by `chainid`
+    V2,
+}
+
+impl FromStr for ExplorerApiVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "v1" => Ok(Self::V1),
+            "v2" => Ok(Self::V2),
+            _ => Err(format!(
+                "Unknown explorer api version {s:?}, expected \"v1\" or \"v2\""
+            )),
+        }
+    }
+}
+
+/// Explorer and node of single chain, unset values are taken from `etherscan` and `json_rpc`
+/// sections. Known chains (mainnet, sepolia, polygon, arbitrum, optimism, base) are configured
+/// by name alone.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChainConfig {
+    /// EIP-155 chain id, required for chains, which are not known by name
+    pub chain_id: Option<u64>,
+    pub api_version: Option<ExplorerApiVersion>,
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+    /// JSON-RPC node of the chain
+    pub rpc_url: Option<String>,
+}
+
+/// Chain config with defaults filled in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedChainConfig {
+    pub name: String,
+    pub chain_id: u64,
+    pub api_version: ExplorerApiVersion,
+    pub api_url: String,
+    pub api_key: Option<String>,
+    pub rpc_url: Option<String>,
+}
+
+/// Page sizes of web pages and JSON API, requested sizes are capped by `max_*`
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PagesConfig {
+    pub per_page: u64,
+    pub max_per_page: u64,
+    pub api_per_page: u64,
+    pub api_max_per_page: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            log_level: "info".to_string(),
+            server: ServerConfig::default(),
+            database: DatabaseConfig::default(),
+            crawler: CrawlerConfig::default(),
+            etherscan: EtherscanConfig::default(),
+            json_rpc: JsonRpcConfig::default(),
+            pages: PagesConfig::default(),
+            chains: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 8000,
+        }
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            max_connections: 10,
+        }
+    }
+}
+
+impl Default for CrawlerConfig {
+    fn default() -> Self {
+        Self {
+            source: TransactionSourceKind::Etherscan,
+            confirmation_depth: DEFAULT_CONFIRMATION_DEPTH,
+            reorg_recheck_blocks: DEFAULT_REORG_RECHECK_BLOCKS,
+            watch_interval_secs: DEFAULT_WATCH_INTERVAL.as_secs(),
+        }
+    }
+}
+
+impl Default for EtherscanConfig {
+    fn default() -> Self {
+        Self {
+            api_key: None,
+            api_version: ExplorerApiVersion::V1,
+            api_url: None,
+            timeout_secs: DEFAULT_TIMEOUT.as_secs(),
+            requests_per_second: DEFAULT_REQUESTS_PER_SECOND,
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+}
+
+impl Default for JsonRpcConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            timeout_secs: DEFAULT_RPC_TIMEOUT.as_secs(),
+        }
+    }
+}
+
+impl Default for PagesConfig {
+    fn default() -> Self {
+        Self {
+            per_page: 10,
+            max_per_page: 100,
+            api_per_page: 100,
+            api_max_per_page: 1000,
+        }
+    }
+}
+
+impl PagesConfig {
+    /// Rows per page of web page, requested or default one, within cap
+    pub fn per_page(&self, requested: Option<u64>) -> u64 {
+        requested
+            .unwrap_or(self.per_page)
+            .clamp(1, self.max_per_page)
+    }
+
+    /// Rows per page of JSON API, requested or default one, within cap
+    pub fn api_per_page(&self, requested: Option<u64>) -> u64 {
+        requested
+            .unwrap_or(self.api_per_page)
+            .clamp(1, self.api_max_per_page)
+    }
+}
+
+impl Config {
+    /// Loads config file and applies environment overrides.
+    /// Given file must exist, default one is skipped if missing.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        dotenvy::dotenv().ok();
+
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| env::var_os("CONFIG_FILE").map(PathBuf::from));
+
+        let mut config = match path {
+            Some(path) => Self::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            None => Self::default(),
+        };
+        config.apply_env()?;
+
+        Ok(config)
+    }
+
+    /// Reads TOML config file, missing keys get default values
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(|err| ConfigError::ReadFailed {
+            path: path.display().to_string(),
+            message: err.to_string(),
+        })?;
+
+        toml::from_str(&contents).map_err(|err| ConfigError::InvalidFile {
+            path: path.display().to_string(),
+            message: err.to_string(),
+        })
+    }
+
+    /// Overrides values with environment variables, which are set
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        override_from_env("LOG_LEVEL", &mut self.log_level)?;
+        override_from_env("HOST", &mut self.server.host)?;
+        override_from_env("PORT", &mut self.server.port)?;
+        override_optional_from_env("DATABASE_URL", &mut self.database.url);
+        override_from_env(
+            "DATABASE_MAX_CONNECTIONS",
+            &mut self.database.max_connections,
+        )?;
+        override_from_env("TRANSACTION_SOURCE", &mut self.crawler.source)?;
+        override_from_env("CONFIRMATION_DEPTH", &mut self.crawler.confirmation_depth)?;
+        override_from_env(
+            "REORG_RECHECK_BLOCKS",
+            &mut self.crawler.reorg_recheck_blocks,
+        )?;
+        override_from_env("WATCH_INTERVAL_SECS", &mut self.crawler.watch_interval_secs)?;
+        override_optional_from_env("ETHERSCAN_API_KEY", &mut self.etherscan.api_key);
+        override_from_env("ETHERSCAN_API_VERSION", &mut self.etherscan.api_version)?;
+        override_optional_from_env("ETHERSCAN_API_URL", &mut self.etherscan.api_url);
+        override_from_env("ETHERSCAN_TIMEOUT_SECS", &mut self.etherscan.timeout_secs)?;
+        override_from_env(
+            "ETHERSCAN_REQUESTS_PER_SECOND",
+            &mut self.etherscan.requests_per_second,
+        )?;
+        override_from_env("ETHERSCAN_MAX_RETRIES", &mut self.etherscan.max_retries)?;
+        override_optional_from_env("ETH_RPC_URL", &mut self.json_rpc.url);
+        override_from_env("ETH_RPC_TIMEOUT_SECS", &mut self.json_rpc.timeout_secs)?;
+        override_from_env("PER_PAGE", &mut self.pages.per_page)?;
+        override_from_env("MAX_PER_PAGE", &mut self.pages.max_per_page)?;
+        override_from_env("API_PER_PAGE", &mut self.pages.api_per_page)?;
+        override_from_env("API_MAX_PER_PAGE", &mut self.pages.api_max_per_page)?;
+
+        Ok(())
+    }
+
+    /// Checks the whole config at once, so every problem is reported on startup
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        if let Err(err) = EnvFilter::try_new(&self.log_level) {
+            problems.push(format!("log_level {:?} is invalid: {err}", self.log_level));
+        }
+        if self.server.host.is_empty() {
+            problems.push("server.host (HOST) is empty".to_string());
+        }
+        if self.database.url.as_deref().unwrap_or_default().is_empty() {
+            problems.push("database.url (DATABASE_URL) is not set".to_string());
+        }
+        if self.database.max_connections == 0 {
+            problems.push(
+                "database.max_connections (DATABASE_MAX_CONNECTIONS) must be positive".to_string(),
+            );
+        }
+        if self.crawler.watch_interval_secs == 0 {
+            problems.push(
+                "crawler.watch_interval_secs (WATCH_INTERVAL_SECS) must be positive".to_string(),
+            );
+        }
+
+        match self.chains() {
+            Ok(chains) => {
+                for chain in chains {
+                    self.validate_chain_source(&chain, &mut problems);
+                }
+            }
+            Err(chain_problems) => problems.extend(chain_problems),
+        }
+
+        if self.etherscan.timeout_secs == 0 {
+            problems.push(
+                "etherscan.timeout_secs (ETHERSCAN_TIMEOUT_SECS) must be positive".to_string(),
+            );
+        }
+        if !(self.etherscan.requests_per_second.is_finite()
+            && self.etherscan.requests_per_second > 0.0)
+        {
+            problems.push(
+                "etherscan.requests_per_second (ETHERSCAN_REQUESTS_PER_SECOND) must be positive"
+                    .to_string(),
+            );
+        }
+        if self.json_rpc.timeout_secs == 0 {
+            problems
+                .push("json_rpc.timeout_secs (ETH_RPC_TIMEOUT_SECS) must be positive".to_string());
+        }
+
+        if !(1..=self.pages.max_per_page).contains(&self.pages.per_page) {
+            problems.push(format!(
+                "pages.per_page (PER_PAGE) must be between 1 and pages.max_per_page ({})",
+                self.pages.max_per_page
+            ));
+        }
+        if !(1..=self.pages.api_max_per_page).contains(&self.pages.api_per_page) {
+            problems.push(format!(
+                "pages.api_per_page (API_PER_PAGE) must be between 1 and pages.api_max_per_page ({})",
+                self.pages.api_max_per_page
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid { problems })
+        }
+    }
+}
+
+impl Config {
+    /// Configured chains with defaults filled in, sorted by name.
+    /// Mainnet with top level explorer and node settings is used if no chains are configured.
+    pub fn chains(&self) -> Result<Vec<ResolvedChainConfig>, Vec<String>> {
+        let implicit_mainnet = BTreeMap::from([(MAINNET.name.to_string(), ChainConfig::default())]);
+        let chains = if self.chains.is_empty() {
+            &implicit_mainnet
+        } else {
+            &self.chains
+        };
+
+        let mut resolved = vec![];
+        let mut problems = vec![];
+        let mut chain_ids = HashSet::new();
+
+        for (name, chain) in chains {
+            let known = KnownChain::by_name(name);
+
+            if name.is_empty()
+                || name.bytes().all(|b| b.is_ascii_digit())
+                || !name
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_')
+            {
+                problems.push(format!(
+                    "chains.{name}: name must consist of lowercase letters, digits, '-' and '_' \
+                     and can't be a number"
+                ));
+            }
+
+            let Some(chain_id) = chain.chain_id.or(known.map(|known| known.chain_id)) else {
+                problems.push(format!(
+                    "chains.{name}.chain_id is required for chain, which is not known by name"
+                ));
+                continue;
+            };
+            if !chain_ids.insert(chain_id) {
+                problems.push(format!(
+                    "chains.{name}: chain id {chain_id} is configured more than once"
+                ));
+            }
+
+            let api_version = chain.api_version.unwrap_or(self.etherscan.api_version);
+            let api_url = chain
+                .api_url
+                .clone()
+                .or_else(|| self.etherscan.api_url.clone())
+                .or_else(|| match api_version {
+                    ExplorerApiVersion::V1 => known.map(|known| known.explorer_url.to_string()),
+                    ExplorerApiVersion::V2 => Some(ETHERSCAN_V2_API.to_string()),
+                });
+            let Some(api_url) = api_url else {
+                problems.push(format!(
+                    "chains.{name}.api_url is required for v1 api of chain, which is not known by name"
+                ));
+                continue;
+            };
+
+            resolved.push(ResolvedChainConfig {
+                name: name.clone(),
+                chain_id,
+                api_version,
+                api_url,
+                api_key: chain
+                    .api_key
+                    .clone()
+                    .or_else(|| self.etherscan.api_key.clone()),
+                rpc_url: chain.rpc_url.clone().or_else(|| {
+                    (chain_id == MAINNET.chain_id)
+                        .then(|| self.json_rpc.url.clone())
+                        .flatten()
+                }),
+            });
+        }
+
+        if problems.is_empty() {
+            Ok(resolved)
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Checks that chain has the client of selected transaction source
+    fn validate_chain_source(&self, chain: &ResolvedChainConfig, problems: &mut Vec<String>) {
+        // Without configured chains settings come from top level sections alone
+        let configured = !self.chains.is_empty();
+
+        match self.crawler.source {
+            TransactionSourceKind::Etherscan if chain.api_key.is_none() => {
+                problems.push(if configured {
+                    format!(
+                        "chains.{}.api_key or etherscan.api_key (ETHERSCAN_API_KEY) is required \
+                         for etherscan source",
+                        chain.name
+                    )
+                } else {
+                    "etherscan.api_key (ETHERSCAN_API_KEY) is required for etherscan source"
+                        .to_string()
+                })
+            }
+            TransactionSourceKind::JsonRpc if chain.rpc_url.is_none() => {
+                problems.push(if configured && chain.chain_id != MAINNET.chain_id {
+                    format!(
+                        "chains.{}.rpc_url is required for json_rpc source",
+                        chain.name
+                    )
+                } else if configured {
+                    format!(
+                        "chains.{}.rpc_url or json_rpc.url (ETH_RPC_URL) is required \
+                         for json_rpc source",
+                        chain.name
+                    )
+                } else {
+                    "json_rpc.url (ETH_RPC_URL) is required for json_rpc source".to_string()
+                })
+            }
+            _ => {}
+        }
+    }
+}
+
+fn override_from_env<T>(name: &str, value: &mut T) -> Result<(), ConfigError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    if let Ok(raw) = env::var(name) {
+        *value = raw
+            .parse()
+            .map_err(|err: T::Err| ConfigError::InvalidEnvVar {
+                name: name.to_string(),
+                value: raw.clone(),
+                message: err.to_string(),
+            })?;
+    }
+
+    Ok(())
+}
+
+fn override_optional_from_env(name: &str, value: &mut Option<String>) {
+    if let Ok(raw) = env::var(name) {
+        *value = Some(raw);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes config file into temp dir, every test uses its own file name
+    fn config_file(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("eth_crawler_{}_{name}.toml", std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// Makes valid config invalid in one way
+    type BreakConfig = fn(&mut Config);
+
+    fn valid_config() -> Config {
+        let mut config = Config::default();
+        config.database.url = Some("sqlite::memory:".to_string());
+        config.etherscan.api_key = Some("key".to_string());
+        config
+    }
+
+    fn problems(config: &Config) -> Vec<String> {
+        match config.validate() {
+            Ok(()) => vec![],
+            Err(ConfigError::Invalid { problems }) => problems,
+            Err(err) => panic!("unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn file_overrides_defaults() {
+        let path = config_file(
+            "file",
+            r#"
+            [server]
+            port = 9000
+
+            [crawler]
+            reorg_recheck_blocks = 64
+
+            [chains.polygon]
+            api_key = "polygon key"
+            "#,
+        );
+        let config = Config::from_file(&path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(config.server.port, 9000);
+        assert_eq!(config.server.host, "127.0.0.1");
+        assert_eq!(config.crawler.reorg_recheck_blocks, 64);
+        assert_eq!(
+            config.crawler.confirmation_depth,
+            DEFAULT_CONFIRMATION_DEPTH
+        );
+        assert_eq!(
+            config.chains["polygon"].api_key.as_deref(),
+            Some("polygon key")
+        );
+    }
+
+    #[test]
+    fn mistyped_file_values_are_rejected() {
+        for (name, contents) in [
+            ("datetime", "log_level = 1979-05-27T07:32:00Z"),
+            ("integer", "[server]\nhost = 1"),
+            ("unknown", "[crawler]\nconfirmation_blocks = 1"),
+        ] {
+            let path = config_file(name, contents);
+            let result = Config::from_file(&path);
+            fs::remove_file(path).unwrap();
+
+            assert!(
+                matches!(result, Err(ConfigError::InvalidFile { .. })),
+                "{contents:?} is accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn env_overrides_file() {
+        let path = config_file("env", "[crawler]\nreorg_recheck_blocks = 64");
+        let mut config = Config::from_file(&path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        env::set_var("REORG_RECHECK_BLOCKS", "32");
+        let result = config.apply_env();
+        env::remove_var("REORG_RECHECK_BLOCKS");
+
+        result.unwrap();
+        assert_eq!(config.crawler.reorg_recheck_blocks, 32);
+    }
+
+    #[test]
+    fn valid_config_has_no_problems() {
+        assert!(problems(&valid_config()).is_empty());
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let mut config = valid_config();
+        config.server.host.clear();
+        config.database.url = None;
+        config.crawler.watch_interval_secs = 0;
+
+        assert_eq!(
+            problems(&config),
+            [
+                "server.host (HOST) is empty",
+                "database.url (DATABASE_URL) is not set",
+                "crawler.watch_interval_secs (WATCH_INTERVAL_SECS) must be positive",
+            ]
+        );
+    }
+
+    #[test]
+    fn problem_messages() {
+        let cases: [(BreakConfig, &str); 13] = [
+            (
+                |config| config.database.url = Some(String::new()),
+                "database.url (DATABASE_URL) is not set",
+            ),
+            (
+                |config| config.database.max_connections = 0,
+                "database.max_connections (DATABASE_MAX_CONNECTIONS) must be positive",
+            ),
+            (
+                |config| config.etherscan.api_key = None,
+                "etherscan.api_key (ETHERSCAN_API_KEY) is required for etherscan source",
+            ),
+            (
+                |config| config.crawler.source = TransactionSourceKind::JsonRpc,
+                "json_rpc.url (ETH_RPC_URL) is required for json_rpc source",
+            ),
+            (
+                |config| config.etherscan.timeout_secs = 0,
+                "etherscan.timeout_secs (ETHERSCAN_TIMEOUT_SECS) must be positive",
+            ),
+            (
+                |config| config.etherscan.requests_per_second = f64::NAN,
+                "etherscan.requests_per_second (ETHERSCAN_REQUESTS_PER_SECOND) must be positive",
+            ),
+            (
+                |config| config.json_rpc.timeout_secs = 0,
+                "json_rpc.timeout_secs (ETH_RPC_TIMEOUT_SECS) must be positive",
+            ),
+            (
+                |config| config.pages.per_page = 101,
+                "pages.per_page (PER_PAGE) must be between 1 and pages.max_per_page (100)",
+            ),
+            (
+                |config| config.pages.api_per_page = 0,
+                "pages.api_per_page (API_PER_PAGE) must be between 1 and pages.api_max_per_page (1000)",
+            ),
+            (
+                |config| {
+                    config.chains.insert(
+                        "Polygon".to_string(),
+                        ChainConfig {
+                            chain_id: Some(137),
+                            api_url: Some("https://api.polygonscan.com/api".to_string()),
+                            ..Default::default()
+                        },
+                    );
+                },
+                "chains.Polygon: name must consist of lowercase letters, digits, '-' and '_' \
+                 and can't be a number",
+            ),
+            (
+                |config| {
+                    config
+                        .chains
+                        .insert("devnet".to_string(), ChainConfig::default());
+                },
+                "chains.devnet.chain_id is required for chain, which is not known by name",
+            ),
+            (
+                |config| {
+                    config.chains.insert(
+                        "devnet".to_string(),
+                        ChainConfig {
+                            chain_id: Some(1337),
+                            ..Default::default()
+                        },
+                    );
+                },
+                "chains.devnet.api_url is required for v1 api of chain, which is not known by name",
+            ),
+            (
+                |config| {
+                    config
+                        .chains
+                        .insert("mainnet".to_string(), ChainConfig::default());
+                    config.chains.insert(
+                        "eth".to_string(),
+                        ChainConfig {
+                            chain_id: Some(1),
+                            api_url: Some("http://127.0.0.1:8080/api".to_string()),
+                            ..Default::default()
+                        },
+                    );
+                },
+                "chains.mainnet: chain id 1 is configured more than once",
+            ),
+        ];
+
+        for (break_config, problem) in cases {
+            let mut config = valid_config();
+            break_config(&mut config);
+
+            assert_eq!(problems(&config), [problem]);
+        }
+    }
+
+    #[test]
+    fn invalid_log_level_is_reported() {
+        let mut config = valid_config();
+        config.log_level = "actix_web=loud".to_string();
+
+        let problems = problems(&config);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("log_level \"actix_web=loud\" is invalid: "));
+    }
+
+    #[test]
+    fn source_problems_name_chain() {
+        let mut config = valid_config();
+        config.etherscan.api_key = None;
+        config
+            .chains
+            .insert("polygon".to_string(), ChainConfig::default());
+        assert_eq!(
+            problems(&config),
+            [
+                "chains.polygon.api_key or etherscan.api_key (ETHERSCAN_API_KEY) is required \
+              for etherscan source"
+            ]
+        );
+
+        config.crawler.source = TransactionSourceKind::JsonRpc;
+        config
+            .chains
+            .insert("mainnet".to_string(), ChainConfig::default());
+        assert_eq!(
+            problems(&config),
+            [
+                "chains.mainnet.rpc_url or json_rpc.url (ETH_RPC_URL) is required for json_rpc source",
+                "chains.polygon.rpc_url is required for json_rpc source",
+            ]
+        );
+    }
+}
@@ -0,0 +1,660 @@
+use crate::{
+    errors::ServerError,
+    eth_api::EtherscanClient,
+    json_rpc::JsonRpcClient,
+    transaction_source::{FetchProgress, TransactionSource},
+    validators::*,
+    TransactionFormInput,
+};
+use actix_example_service::{sea_orm::DatabaseConnection, Mutation, Query};
+use entity::crawl_coverage::CoverageSource;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+// Uncovered block ranges are crawled in chunks of at most this many blocks,
+// so that results and coverage are saved while a long crawl goes on
+pub const MAX_BLOCKS_PER_RANGE: u64 = 1_000_000;
+
+// Default number of blocks, which must be mined on top of a block before it's crawled
+pub const DEFAULT_CONFIRMATION_DEPTH: u64 = 12;
+
+// Number of saved transactions, whose missing fee breakdown is filled at once
+const FEE_BACKFILL_BATCH_SIZE: u64 = 256;
+
+// Default number of latest blocks, which are re-checked for reorgs
+pub const DEFAULT_REORG_RECHECK_BLOCKS: u64 = 128;
+
+/// Progress counters of crawl job, persisted after every crawled block range
+#[derive(Debug)]
+pub struct CrawlProgress {
+    job_id: i32,
+    ranges_total: AtomicU64,
+    ranges_done: AtomicU64,
+    pages_fetched: AtomicU64,
+    rows_saved: AtomicU64,
+}
+
+impl CrawlProgress {
+    pub fn new(job_id: i32) -> Self {
+        Self {
+            job_id,
+            ranges_total: AtomicU64::new(0),
+            ranges_done: AtomicU64::new(0),
+            pages_fetched: AtomicU64::new(0),
+            rows_saved: AtomicU64::new(0),
+        }
+    }
+
+    async fn ranges_planned(
+        &self,
+        conn: &DatabaseConnection,
+        ranges_count: usize,
+    ) -> Result<(), ServerError> {
+        self.ranges_total
+            .fetch_add(ranges_count as u64, Ordering::Relaxed);
+
+        self.save(conn).await
+    }
+
+    async fn range_saved(
+        &self,
+        conn: &DatabaseConnection,
+        rows_count: usize,
+    ) -> Result<(), ServerError> {
+        self.ranges_done.fetch_add(1, Ordering::Relaxed);
+        self.rows_saved
+            .fetch_add(rows_count as u64, Ordering::Relaxed);
+
+        self.save(conn).await
+    }
+
+    async fn save(&self, conn: &DatabaseConnection) -> Result<(), ServerError> {
+        Mutation::update_crawl_job_progress(
+            conn,
+            self.job_id,
+            self.ranges_total.load(Ordering::Relaxed),
+            self.ranges_done.load(Ordering::Relaxed),
+            self.pages_fetched.load(Ordering::Relaxed),
+            self.rows_saved.load(Ordering::Relaxed),
+        )
+        .await
+        .map_err(ServerError::from)
+    }
+}
+
+impl FetchProgress for CrawlProgress {
+    fn page_fetched(&self) {
+        self.pages_fetched.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Runs all crawl passes for given address since starting block up to ending block, if any,
+/// and at most up to the last block, which has `confirmation_depth` blocks on top of it.
+/// Passes, which rely on Etherscan specific endpoints, are skipped if Etherscan is not configured.
+/// Fee breakdown is filled from JSON-RPC node if it's configured, also for transactions,
+/// which were saved before it was configured.
+/// Hashes of crawled blocks within `reorg_recheck_blocks` latest blocks are recorded for reorg re-checks.
+#[allow(clippy::too_many_arguments)]
+pub async fn crawl(
+    conn: &DatabaseConnection,
+    source: &dyn TransactionSource,
+    etherscan: Option<&EtherscanClient>,
+    json_rpc: Option<&JsonRpcClient>,
+    form: &TransactionFormInput,
+    confirmation_depth: u64,
+    reorg_recheck_blocks: u64,
+    progress: &CrawlProgress,
+) -> Result<(), ServerError> {
+    let current_block_number = source.get_current_block_number().await?;
+
+    ensure_valid_starting_block_number(form.starting_block_number, current_block_number)?;
+
+    // Unconfirmed blocks may still be reorged away, they are crawled by later runs
+    let last_block_number = current_block_number
+        .saturating_sub(confirmation_depth)
+        .min(form.ending_block_number.unwrap_or(u64::MAX));
+
+    // Blocks before it are no longer re-checked for reorgs by the time crawl finishes
+    let recheck_from_block = current_block_number.saturating_sub(reorg_recheck_blocks);
+    record_block_hashes(
+        conn,
+        form.chain_id,
+        source,
+        (
+            recheck_from_block.max(form.starting_block_number),
+            last_block_number,
+        ),
+    )
+    .await?;
+
+    crawl_transactions(conn, source, json_rpc, form, last_block_number, progress).await?;
+
+    if let Some(etherscan) = etherscan {
+        crawl_internal_transactions(conn, etherscan, form, last_block_number, progress).await?;
+        crawl_token_transfers(conn, etherscan, form, last_block_number, progress).await?;
+        crawl_nft_transfers(conn, etherscan, form, last_block_number, progress).await?;
+    }
+
+    Ok(())
+}
+
+/// Compares hashes of every block of chain within `recheck_blocks` latest blocks against the ones
+/// recorded when blocks were crawled or last re-checked. Since the first orphaned block, rows
+/// are deleted and blocks are removed from coverage. Current hashes are recorded for the next re-check.
+/// Returns addresses to crawl again, along with the first orphaned block.
+pub async fn recheck_recent_blocks(
+    conn: &DatabaseConnection,
+    chain_id: u64,
+    source: &dyn TransactionSource,
+    recheck_blocks: u64,
+) -> Result<Vec<(String, u64)>, ServerError> {
+    let current_block_number = source.get_current_block_number().await?;
+    let recheck_from_block = current_block_number.saturating_sub(recheck_blocks);
+
+    // One block may have been seen with several hashes
+    let mut known_block_hashes: HashMap<u64, Vec<String>> = HashMap::new();
+    for (block_number, block_hash) in
+        Query::find_block_hashes_since_block(conn, chain_id, recheck_from_block)
+            .await
+            .map_err(ServerError::from)?
+    {
+        known_block_hashes
+            .entry(block_number)
+            .or_default()
+            .push(block_hash);
+    }
+
+    let mut canonical_block_hashes = vec![];
+    let mut first_orphaned_block = None;
+
+    for block_number in recheck_from_block..=current_block_number {
+        let canonical_block_hash = source.get_block_hash(block_number).await?;
+
+        if first_orphaned_block.is_none() {
+            if let Some(block_hash) = known_block_hashes
+                .get(&block_number)
+                .into_iter()
+                .flatten()
+                .find(|block_hash| !block_hash.eq_ignore_ascii_case(&canonical_block_hash))
+            {
+                println!(
+                    "Block {block_number} was reorged, {block_hash} is replaced by {canonical_block_hash}"
+                );
+                first_orphaned_block = Some(block_number);
+            }
+        }
+
+        canonical_block_hashes.push((block_number, canonical_block_hash));
+    }
+
+    let mut addresses_to_recrawl = vec![];
+
+    if let Some(first_orphaned_block) = first_orphaned_block {
+        addresses_to_recrawl =
+            Mutation::remove_orphaned_blocks(conn, chain_id, first_orphaned_block)
+                .await
+                .map_err(ServerError::from)?
+                .into_iter()
+                .map(|address| (address, first_orphaned_block))
+                .collect();
+    }
+
+    Mutation::save_block_hashes(conn, chain_id, canonical_block_hashes)
+        .await
+        .map_err(ServerError::from)?;
+    Mutation::remove_block_hashes_before(conn, chain_id, recheck_from_block)
+        .await
+        .map_err(ServerError::from)?;
+
+    Ok(addresses_to_recrawl)
+}
+
+/// Records hashes of blocks of chain within block range, which are not recorded yet.
+/// It's done before blocks are fetched, so that a reorg during crawl is noticed by the next re-check.
+async fn record_block_hashes(
+    conn: &DatabaseConnection,
+    chain_id: u64,
+    source: &dyn TransactionSource,
+    (start, end): (u64, u64),
+) -> Result<(), ServerError> {
+    if start > end {
+        return Ok(());
+    }
+
+    let recorded_block_numbers: HashSet<u64> =
+        Query::find_recorded_block_numbers(conn, chain_id, (start, end))
+            .await
+            .map_err(ServerError::from)?
+            .into_iter()
+            .collect();
+
+    let mut block_hashes = vec![];
+    for block_number in start..=end {
+        if !recorded_block_numbers.contains(&block_number) {
+            block_hashes.push((block_number, source.get_block_hash(block_number).await?));
+        }
+    }
+
+    Mutation::save_block_hashes(conn, chain_id, block_hashes)
+        .await
+        .map_err(ServerError::from)
+}
+
+/// Fetches and saves transactions of given address in block ranges, which were not fetched yet.
+/// Transactions are saved range by range, so they show up while crawl goes on,
+/// and every saved range is recorded as covered.
+pub async fn crawl_transactions(
+    conn: &DatabaseConnection,
+    source: &dyn TransactionSource,
+    fee_source: Option<&JsonRpcClient>,
+    form: &TransactionFormInput,
+    last_block_number: u64,
+    progress: &CrawlProgress,
+) -> Result<(), ServerError> {
+    let block_ranges_for_unfetched_transactions = split_block_ranges(
+        Query::find_uncovered_block_ranges(
+            conn,
+            form.chain_id,
+            &form.address,
+            CoverageSource::Transactions,
+            (form.starting_block_number, last_block_number),
+        )
+        .await
+        .map_err(ServerError::from)?,
+    );
+
+    progress
+        .ranges_planned(conn, block_ranges_for_unfetched_transactions.len())
+        .await?;
+
+    for block_range_for_unfetched_transactions in block_ranges_for_unfetched_transactions {
+        let mut unfetched_transactions = source
+            .fetch_transactions(block_range_for_unfetched_transactions, form, progress)
+            .await?;
+
+        if let Some(fee_source) = fee_source {
+            fee_source
+                .fill_fee_details(&mut unfetched_transactions)
+                .await?;
+        }
+        let unfetched_transactions_count = unfetched_transactions.len();
+
+        if !unfetched_transactions.is_empty() {
+            Mutation::save_transactions(conn, unfetched_transactions)
+                .await
+                .map_err(ServerError::from)?;
+        }
+
+        Mutation::add_crawl_coverage(
+            conn,
+            form.chain_id,
+            &form.address,
+            CoverageSource::Transactions,
+            block_range_for_unfetched_transactions,
+        )
+        .await
+        .map_err(ServerError::from)?;
+
+        progress
+            .range_saved(conn, unfetched_transactions_count)
+            .await?;
+    }
+
+    if let Some(fee_source) = fee_source {
+        backfill_fee_details(conn, fee_source, form, last_block_number).await?;
+    }
+
+    Ok(())
+}
+
+/// Fills fee breakdown of saved transactions of given address, which were crawled
+/// before JSON-RPC node was configured, batch by batch
+async fn backfill_fee_details(
+    conn: &DatabaseConnection,
+    fee_source: &JsonRpcClient,
+    form: &TransactionFormInput,
+    last_block_number: u64,
+) -> Result<(), ServerError> {
+    loop {
+        let mut transactions = Query::find_transactions_without_fee_details(
+            conn,
+            form.chain_id,
+            &form.address,
+            (form.starting_block_number, last_block_number),
+            FEE_BACKFILL_BATCH_SIZE,
+        )
+        .await
+        .map_err(ServerError::from)?;
+
+        if transactions.is_empty() {
+            return Ok(());
+        }
+
+        // Every filled transaction gets effective gas price, so it isn't found again
+        fee_source.fill_fee_details(&mut transactions).await?;
+
+        Mutation::update_fee_details(conn, transactions)
+            .await
+            .map_err(ServerError::from)?;
+    }
+}
+
+/// Fetches and saves internal transactions of given address, which were not fetched yet
+pub async fn crawl_internal_transactions(
+    conn: &DatabaseConnection,
+    etherscan: &EtherscanClient,
+    form: &TransactionFormInput,
+    last_block_number: u64,
+    progress: &CrawlProgress,
+) -> Result<(), ServerError> {
+    let block_ranges_for_unfetched_internal_transactions = split_block_ranges(
+        Query::find_uncovered_block_ranges(
+            conn,
+            form.chain_id,
+            &form.address,
+            CoverageSource::InternalTransactions,
+            (form.starting_block_number, last_block_number),
+        )
+        .await
+        .map_err(ServerError::from)?,
+    );
+
+    progress
+        .ranges_planned(conn, block_ranges_for_unfetched_internal_transactions.len())
+        .await?;
+
+    for block_range_for_unfetched_internal_transactions in
+        block_ranges_for_unfetched_internal_transactions
+    {
+        let unfetched_internal_transactions = etherscan
+            .fetch_internal_transactions(
+                block_range_for_unfetched_internal_transactions,
+                form,
+                progress,
+            )
+            .await?;
+        let unfetched_internal_transactions_count = unfetched_internal_transactions.len();
+
+        if !unfetched_internal_transactions.is_empty() {
+            Mutation::save_internal_transactions(conn, unfetched_internal_transactions)
+                .await
+                .map_err(ServerError::from)?;
+        }
+
+        Mutation::add_crawl_coverage(
+            conn,
+            form.chain_id,
+            &form.address,
+            CoverageSource::InternalTransactions,
+            block_range_for_unfetched_internal_transactions,
+        )
+        .await
+        .map_err(ServerError::from)?;
+
+        progress
+            .range_saved(conn, unfetched_internal_transactions_count)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Fetches and saves ERC-20 token transfers of given address, which were not fetched yet
+pub async fn crawl_token_transfers(
+    conn: &DatabaseConnection,
+    etherscan: &EtherscanClient,
+    form: &TransactionFormInput,
+    last_block_number: u64,
+    progress: &CrawlProgress,
+) -> Result<(), ServerError> {
+    let block_ranges_for_unfetched_token_transfers = split_block_ranges(
+        Query::find_uncovered_block_ranges(
+            conn,
+            form.chain_id,
+            &form.address,
+            CoverageSource::TokenTransfers,
+            (form.starting_block_number, last_block_number),
+        )
+        .await
+        .map_err(ServerError::from)?,
+    );
+
+    progress
+        .ranges_planned(conn, block_ranges_for_unfetched_token_transfers.len())
+        .await?;
+
+    for block_range_for_unfetched_token_transfers in block_ranges_for_unfetched_token_transfers {
+        let (unfetched_token_transfers, transferred_tokens) = etherscan
+            .fetch_token_transfers(block_range_for_unfetched_token_transfers, form, progress)
+            .await?;
+        let unfetched_token_transfers_count = unfetched_token_transfers.len();
+
+        // Tokens go first, transfers reference them
+        if !transferred_tokens.is_empty() {
+            Mutation::save_tokens(conn, transferred_tokens)
+                .await
+                .map_err(ServerError::from)?;
+        }
+
+        if !unfetched_token_transfers.is_empty() {
+            Mutation::save_token_transfers(conn, unfetched_token_transfers)
+                .await
+                .map_err(ServerError::from)?;
+        }
+
+        Mutation::add_crawl_coverage(
+            conn,
+            form.chain_id,
+            &form.address,
+            CoverageSource::TokenTransfers,
+            block_range_for_unfetched_token_transfers,
+        )
+        .await
+        .map_err(ServerError::from)?;
+
+        progress
+            .range_saved(conn, unfetched_token_transfers_count)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Fetches and saves NFT transfers of given address, which were not fetched yet
+pub async fn crawl_nft_transfers(
+    conn: &DatabaseConnection,
+    etherscan: &EtherscanClient,
+    form: &TransactionFormInput,
+    last_block_number: u64,
+    progress: &CrawlProgress,
+) -> Result<(), ServerError> {
+    let block_ranges_for_unfetched_nft_transfers = split_block_ranges(
+        Query::find_uncovered_block_ranges(
+            conn,
+            form.chain_id,
+            &form.address,
+            CoverageSource::NftTransfers,
+            (form.starting_block_number, last_block_number),
+        )
+        .await
+        .map_err(ServerError::from)?,
+    );
+
+    progress
+        .ranges_planned(conn, block_ranges_for_unfetched_nft_transfers.len())
+        .await?;
+
+    for block_range_for_unfetched_nft_transfers in block_ranges_for_unfetched_nft_transfers {
+        let unfetched_nft_transfers = etherscan
+            .fetch_nft_transfers(block_range_for_unfetched_nft_transfers, form, progress)
+            .await?;
+        let unfetched_nft_transfers_count = unfetched_nft_transfers.len();
+
+        if !unfetched_nft_transfers.is_empty() {
+            Mutation::save_nft_transfers(conn, unfetched_nft_transfers)
+                .await
+                .map_err(ServerError::from)?;
+        }
+
+        Mutation::add_crawl_coverage(
+            conn,
+            form.chain_id,
+            &form.address,
+            CoverageSource::NftTransfers,
+            block_range_for_unfetched_nft_transfers,
+        )
+        .await
+        .map_err(ServerError::from)?;
+
+        progress
+            .range_saved(conn, unfetched_nft_transfers_count)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Splits block ranges into chunks of at most `MAX_BLOCKS_PER_RANGE` blocks
+fn split_block_ranges(block_ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    let mut chunks = vec![];
+
+    for (r_start, r_end) in block_ranges {
+        let mut chunk_start = r_start;
+
+        loop {
+            let chunk_end = r_end.min(chunk_start.saturating_add(MAX_BLOCKS_PER_RANGE - 1));
+            chunks.push((chunk_start, chunk_end));
+
+            if chunk_end == r_end {
+                break;
+            }
+            chunk_start = chunk_end + 1;
+        }
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_example_service::sea_orm::{ConnectOptions, Database};
+    use async_trait::async_trait;
+    use entity::{transactions, u256::U256};
+    use migration::{Migrator, MigratorTrait};
+    use std::sync::Mutex;
+
+    const CHAIN_ID: u64 = 1;
+    const ADDRESS: &str = "0x00000000000000000000000000000000000000aa";
+
+    /// Chain without any transactions, whose blocks get new hashes since reorged block
+    #[derive(Debug)]
+    struct ReorgedSource {
+        current_block_number: u64,
+        first_reorged_block: Mutex<Option<u64>>,
+    }
+
+    #[async_trait]
+    impl TransactionSource for ReorgedSource {
+        fn name(&self) -> &'static str {
+            "reorged"
+        }
+
+        async fn get_current_block_number(&self) -> Result<u64, ServerError> {
+            Ok(self.current_block_number)
+        }
+
+        async fn get_block_hash(&self, block_number: u64) -> Result<String, ServerError> {
+            Ok(match *self.first_reorged_block.lock().unwrap() {
+                Some(first_reorged_block) if block_number >= first_reorged_block => {
+                    format!("0xreorged{block_number}")
+                }
+                _ => format!("0xhash{block_number}"),
+            })
+        }
+
+        async fn get_balance(&self, _address: &str) -> Result<U256, ServerError> {
+            Ok(U256::zero())
+        }
+
+        async fn get_block_number_before(&self, _timestamp: i64) -> Result<u64, ServerError> {
+            Ok(0)
+        }
+
+        async fn fetch_transactions(
+            &self,
+            _block_range: (u64, u64),
+            _transaction_form_input: &TransactionFormInput,
+            _progress: &dyn FetchProgress,
+        ) -> Result<Vec<transactions::Model>, ServerError> {
+            Ok(vec![])
+        }
+    }
+
+    #[actix_rt::test]
+    async fn reorg_of_covered_block_without_rows_is_recrawled() {
+        let mut options = ConnectOptions::new("sqlite::memory:");
+        options.max_connections(1);
+        let conn = Database::connect(options).await.unwrap();
+        Migrator::up(&conn, None).await.unwrap();
+
+        let source = ReorgedSource {
+            current_block_number: 100,
+            first_reorged_block: Mutex::new(None),
+        };
+        let form = TransactionFormInput {
+            chain_id: CHAIN_ID,
+            address: ADDRESS.to_string(),
+            starting_block_number: 0,
+            ending_block_number: None,
+        };
+        let job = Mutation::create_crawl_job(&conn, CHAIN_ID, ADDRESS.to_string(), 0, None)
+            .await
+            .unwrap();
+
+        crawl(
+            &conn,
+            &source,
+            None,
+            None,
+            &form,
+            10,
+            50,
+            &CrawlProgress::new(job.id),
+        )
+        .await
+        .unwrap();
+        let uncovered_block_ranges = || {
+            Query::find_uncovered_block_ranges(
+                &conn,
+                CHAIN_ID,
+                ADDRESS,
+                CoverageSource::Transactions,
+                (0, 90),
+            )
+        };
+        assert!(uncovered_block_ranges().await.unwrap().is_empty());
+        assert!(recheck_recent_blocks(&conn, CHAIN_ID, &source, 50)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Address has no rows in reorged blocks, they are noticed by recorded hashes
+        *source.first_reorged_block.lock().unwrap() = Some(80);
+        assert_eq!(
+            recheck_recent_blocks(&conn, CHAIN_ID, &source, 50)
+                .await
+                .unwrap(),
+            [(ADDRESS.to_string(), 80)]
+        );
+        assert_eq!(uncovered_block_ranges().await.unwrap(), [(80, 90)]);
+
+        // Hashes of the new chain are recorded, so the same reorg isn't noticed again
+        assert!(recheck_recent_blocks(&conn, CHAIN_ID, &source, 50)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
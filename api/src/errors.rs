@@ -13,8 +13,18 @@ pub enum ServerError {
     InvalidAddress { address: String },
     #[display(fmt = "Reqwest error: {}", reqwest_error)]
     ReqwestError { reqwest_error: reqwest::Error },
-    #[display(fmt = "Reqwest parsing error happened")]
-    ReqwestParsingError,
+    #[display(fmt = "Malformed explorer response: {}", reason)]
+    MalformedResponse { reason: String },
+    #[display(fmt = "Explorer rate limit reached: {}", message)]
+    RateLimited { message: String },
+    #[display(fmt = "Explorer rejected api key: {}", message)]
+    InvalidApiKey { message: String },
+    #[display(fmt = "Explorer found no results")]
+    NoResults,
+    #[display(fmt = "Explorer can't return that many results at once, narrow down block range")]
+    ResultWindowExceeded,
+    #[display(fmt = "Explorer error: {}", message)]
+    ExplorerError { message: String },
     #[display(
         fmt = "Starting block number {} is greater than current block number {}",
         starting_block_number,
@@ -24,12 +34,56 @@ pub enum ServerError {
         starting_block_number: u64,
         current_block_number: u64,
     },
+    #[display(
+        fmt = "Ending block number {} is less than starting block number {}",
+        ending_block_number,
+        starting_block_number
+    )]
+    InvalidEndingBlockNumber {
+        starting_block_number: u64,
+        ending_block_number: u64,
+    },
+    #[display(fmt = "Invalid block number or date (YYYY-MM-DD expected): {}", value)]
+    InvalidBlockOrDate { value: String },
+    #[display(fmt = "Invalid {}: {}", name, value)]
+    InvalidParameter { name: String, value: String },
+    #[display(fmt = "Chain {} is not configured", chain)]
+    UnknownChain { chain: String },
+    #[display(fmt = "Transaction {} not found", hash)]
+    TransactionNotFound { hash: String },
+    #[display(fmt = "Crawl job {} not found", id)]
+    CrawlJobNotFound { id: i32 },
+    #[display(fmt = "Watched address {} not found", id)]
+    WatchedAddressNotFound { id: i32 },
+    #[display(fmt = "Address {} is already watched", address)]
+    AddressAlreadyWatched { address: String },
+    #[display(fmt = "Crawl worker is not running")]
+    CrawlQueueClosed,
+    #[display(fmt = "Export error: {}", message)]
+    ExportError { message: String },
     #[display(fmt = "Database error: {}", db_err)]
     DbErr { db_err: DbErr },
     #[display(fmt = "Parsing error: {}", parse_int_error)]
     ParseIntError { parse_int_error: ParseIntError },
 }
 
+impl ServerError {
+    /// Transient failures, which are worth retrying
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RateLimited { .. } => true,
+            Self::ReqwestError { reqwest_error } => {
+                reqwest_error.is_timeout()
+                    || reqwest_error.is_connect()
+                    || reqwest_error
+                        .status()
+                        .is_some_and(|status| status.is_server_error())
+            }
+            _ => false,
+        }
+    }
+}
+
 impl From<reqwest::Error> for ServerError {
     fn from(reqwest_error: reqwest::Error) -> Self {
         Self::ReqwestError { reqwest_error }
@@ -58,10 +112,100 @@ impl error::ResponseError for ServerError {
         match *self {
             Self::InvalidAddress { .. } => StatusCode::BAD_REQUEST,
             Self::InvalidStartingBlockNumber { .. } => StatusCode::BAD_REQUEST,
-            Self::ReqwestError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
-            Self::ReqwestParsingError => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::InvalidEndingBlockNumber { .. } => StatusCode::BAD_REQUEST,
+            Self::InvalidBlockOrDate { .. } => StatusCode::BAD_REQUEST,
+            Self::InvalidParameter { .. } => StatusCode::BAD_REQUEST,
+            Self::ReqwestError { ref reqwest_error } => {
+                if reqwest_error.is_timeout() {
+                    StatusCode::GATEWAY_TIMEOUT
+                } else if reqwest_error.is_connect()
+                    || reqwest_error.is_request()
+                    || reqwest_error.is_status()
+                    || reqwest_error.is_decode()
+                {
+                    StatusCode::BAD_GATEWAY
+                } else {
+                    // Request couldn't be built locally
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            }
+            Self::MalformedResponse { .. } => StatusCode::BAD_GATEWAY,
+            Self::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Self::InvalidApiKey { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            Self::NoResults => StatusCode::NOT_FOUND,
+            Self::ResultWindowExceeded => StatusCode::BAD_GATEWAY,
+            Self::ExplorerError { .. } => StatusCode::BAD_GATEWAY,
+            Self::UnknownChain { .. } => StatusCode::NOT_FOUND,
+            Self::TransactionNotFound { .. } => StatusCode::NOT_FOUND,
+            Self::CrawlJobNotFound { .. } => StatusCode::NOT_FOUND,
+            Self::WatchedAddressNotFound { .. } => StatusCode::NOT_FOUND,
+            Self::AddressAlreadyWatched { .. } => StatusCode::CONFLICT,
+            Self::CrawlQueueClosed => StatusCode::SERVICE_UNAVAILABLE,
+            Self::ExportError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             Self::DbErr { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             Self::ParseIntError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
+
+/// Error of loading or validating configuration, reported on startup
+#[derive(Debug, Display, Error)]
+pub enum ConfigError {
+    #[display(fmt = "Failed to read config file {}: {}", path, message)]
+    ReadFailed { path: String, message: String },
+    #[display(fmt = "Invalid config file {}: {}", path, message)]
+    InvalidFile { path: String, message: String },
+    #[display(fmt = "Invalid value {:?} of {}: {}", value, name, message)]
+    InvalidEnvVar {
+        name: String,
+        value: String,
+        message: String,
+    },
+    #[display(fmt = "Invalid config:\n  {}", "problems.join(\"\\n  \")")]
+    Invalid { problems: Vec<String> },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::ResponseError;
+    use std::{net::TcpListener, time::Duration};
+
+    #[actix_rt::test]
+    async fn upstream_failures_are_gateway_errors() {
+        // Listener accepts connections, but never responds
+        let silent = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let timeout = client
+            .get(format!("http://{}", silent.local_addr().unwrap()))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            ServerError::from(timeout).status_code(),
+            StatusCode::GATEWAY_TIMEOUT
+        );
+
+        let closed_port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let refused = client
+            .get(format!("http://{closed_port}"))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            ServerError::from(refused).status_code(),
+            StatusCode::BAD_GATEWAY
+        );
+
+        assert_eq!(
+            ServerError::ResultWindowExceeded.status_code(),
+            StatusCode::BAD_GATEWAY
+        );
+    }
+}
@@ -1,171 +1,819 @@
-use crate::{errors::*, TransactionFormInput};
+use crate::{
+    errors::*,
+    rate_limit::*,
+    responses::*,
+    transaction_source::{FetchProgress, TransactionSource},
+    TransactionFormInput,
+};
+use actix_web::rt::time::sleep;
+use async_trait::async_trait;
 use chrono::{TimeZone, Utc};
-use entity::transactions;
+use entity::{
+    internal_transactions, nft_transfers, nft_transfers::NftStandard, token_transfers, tokens,
+    transactions, u256::U256,
+};
+use reqwest::{header::RETRY_AFTER, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
 
-lazy_static! {
-    static ref ETHERSCAN_API_KEY: String =
-        std::env::var("ETHERSCAN_API_KEY").expect("ETHERSCAN_API_KEY is not set in .env file");
+// Default API URL
+pub const ETHERSCAN_API: &str = "https://api.etherscan.io/api";
+
+// Etherscan v2 API URL, serves every supported chain, which is selected by `chainid` parameter
+pub const ETHERSCAN_V2_API: &str = "https://api.etherscan.io/v2/api";
+
+// Default timeouts
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// How long all requests are held back after explorer reported exceeded rate limit
+pub const RATE_LIMIT_PAUSE: Duration = Duration::from_secs(1);
+
+// Number of transactions requested per page
+pub const TRANSACTIONS_PER_PAGE: u64 = 1000;
+
+// Etherscan refuses requests with page * offset above this limit
+pub const RESULT_WINDOW: u64 = 10000;
+
+/// Etherscan API modules
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Module {
+    Account,
+    Block,
+    Proxy,
 }
 
-// API URL
-pub const ETHERSCAN_API: &str = "https://api.etherscan.io/api";
+/// Etherscan API actions
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum Action {
+    #[serde(rename = "txlist")]
+    TxList,
+    #[serde(rename = "txlistinternal")]
+    TxListInternal,
+    #[serde(rename = "tokentx")]
+    TokenTx,
+    #[serde(rename = "tokennfttx")]
+    TokenNftTx,
+    #[serde(rename = "token1155tx")]
+    Token1155Tx,
+    #[serde(rename = "balance")]
+    Balance,
+    #[serde(rename = "getblocknobytime")]
+    GetBlockNoByTime,
+    #[serde(rename = "eth_blockNumber")]
+    EthBlockNumber,
+    #[serde(rename = "eth_getBlockByNumber")]
+    EthGetBlockByNumber,
+}
 
-// Modules
-pub const MODULE_ACCOUNT: &str = "?module=account";
-pub const MODULE_PROXY: &str = "?module=proxy";
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sort {
+    Asc,
+    Desc,
+}
 
-// Actions
-pub const ACTION_TXLIST: &str = "&action=txlist";
-pub const ACTION_ETH_BLOCK_NUMBER: &str = "&action=eth_blockNumber";
+/// Parameters of paginated `module=account` list requests, such as `txlist` and `txlistinternal`
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountListRequest<'a> {
+    pub address: &'a str,
+    #[serde(rename = "startblock")]
+    pub start_block: u64,
+    #[serde(rename = "endblock")]
+    pub end_block: u64,
+    pub page: u32,
+    pub offset: u64,
+    pub sort: Sort,
+}
 
-// Placeholders
-pub const API_KEY_PLACEHOLDER: &str = "&apikey=";
-pub const ADDRESS_PLACEHOLDER: &str = "&address=";
-pub const START_BLOCK_PLACEHOLDER: &str = "&startblock=";
-pub const END_BLOCK_PLACEHOLDER: &str = "&endblock=";
-pub const PAGE_PLACEHOLDER: &str = "&page=";
-pub const OFFSET_PLACEHOLDER: &str = "&offset=";
-pub const SORT_PLACEHOLDER: &str = "&sort=";
+/// Parameters of `module=account&action=balance` request
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceRequest<'a> {
+    pub address: &'a str,
+    pub tag: &'a str,
+}
 
-/// Get current eth block numberv
-pub async fn get_current_block_number() -> Result<u64, ServerError> {
-    let request = ETHERSCAN_API.to_string()
-        + MODULE_PROXY
-        + ACTION_ETH_BLOCK_NUMBER
-        + API_KEY_PLACEHOLDER
-        + &ETHERSCAN_API_KEY;
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Closest {
+    Before,
+    After,
+}
 
-    let resp = reqwest::get(request).await?.json::<Value>().await?;
-    let block_number_hex_str = resp["result"]
-        .as_str()
-        .ok_or(ServerError::ReqwestParsingError)?;
+/// Parameters of `module=block&action=getblocknobytime` request
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockNumberByTimeRequest {
+    pub timestamp: i64,
+    pub closest: Closest,
+}
 
-    let block_number = u64::from_str_radix(block_number_hex_str.trim_start_matches("0x"), 16)
-        .map_err(|_| ServerError::ReqwestParsingError)?;
+/// Parameters of `module=proxy&action=eth_getBlockByNumber` request
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockByNumberRequest {
+    /// Hex encoded block number
+    pub tag: String,
+    /// Whether to return full transaction objects
+    pub boolean: bool,
+}
 
-    Ok(block_number)
+/// Query string sent to Etherscan: chain for v2 API, module, action, action specific parameters
+/// and api key
+#[derive(Serialize)]
+struct EtherscanRequest<'a, P> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chainid: Option<u64>,
+    module: Module,
+    action: Action,
+    #[serde(flatten)]
+    params: P,
+    apikey: &'a str,
 }
 
-pub async fn parse_transactions(
-    transactions: &[Value],
-) -> Result<Vec<transactions::Model>, ServerError> {
-    let mut parsed_transactions = vec![];
-    for transaction in transactions.iter() {
-        let tx_id = transaction["hash"]
-            .as_str()
-            .ok_or(ServerError::ReqwestParsingError)?;
-
-        let address_from = transaction["from"]
-            .as_str()
-            .ok_or(ServerError::ReqwestParsingError)?;
-
-        let address_to = transaction["to"]
-            .as_str()
-            .ok_or(ServerError::ReqwestParsingError)?;
-
-        let value = transaction["value"]
-            .as_str()
-            .map(|value| value.parse::<u64>())
-            .ok_or(ServerError::ReqwestParsingError)??;
-
-        let gas_used = transaction["gasUsed"]
-            .as_str()
-            .map(|gas_used| gas_used.parse::<u64>())
-            .ok_or(ServerError::ReqwestParsingError)??;
-
-        let gas_price = transaction["gasPrice"]
-            .as_str()
-            .map(|gas_price| gas_price.parse::<u64>())
-            .ok_or(ServerError::ReqwestParsingError)??;
-
-        let block_number = transaction["blockNumber"]
-            .as_str()
-            .map(|block_number| block_number.parse::<u64>())
-            .ok_or(ServerError::ReqwestParsingError)??;
-
-        let timestamp = transaction["timeStamp"]
-            .as_str()
-            .map(|timestamp| timestamp.parse::<i64>())
-            .ok_or(ServerError::ReqwestParsingError)??;
-
-        let date_time = Utc.timestamp_opt(timestamp, 0).unwrap();
-
-        let parsed_transaction = transactions::Model {
-            tx_id: tx_id.to_string(),
-            address_from: address_from.to_string(),
-            address_to: address_to.to_string(),
-            value,
-            block_number,
-            date_time: date_time.naive_utc(),
-            tx_fee: gas_used * gas_price,
+/// Builds `EtherscanClient` with custom base url, timeouts, rate limit and retry policy
+#[derive(Debug, Clone)]
+pub struct EtherscanClientBuilder {
+    api_key: String,
+    base_url: String,
+    chain_id: Option<u64>,
+    timeout: Duration,
+    connect_timeout: Duration,
+    requests_per_second: f64,
+    retry_policy: RetryPolicy,
+}
+
+impl EtherscanClientBuilder {
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Chain, which is passed as `chainid` parameter of Etherscan v2 API.
+    /// Per chain explorer hosts don't take it.
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    pub fn requests_per_second(mut self, requests_per_second: f64) -> Self {
+        self.requests_per_second = requests_per_second;
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn build(self) -> Result<EtherscanClient, ServerError> {
+        let client = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
+            .build()?;
+
+        Ok(EtherscanClient {
+            client,
+            base_url: self.base_url,
+            chain_id: self.chain_id,
+            api_key: self.api_key,
+            rate_limiter: RateLimiter::new(self.requests_per_second),
+            retry_policy: self.retry_policy,
+        })
+    }
+}
+
+/// Etherscan API client, reuses single connection pool and rate limiter for all requests
+#[derive(Debug, Clone)]
+pub struct EtherscanClient {
+    client: reqwest::Client,
+    base_url: String,
+    chain_id: Option<u64>,
+    api_key: String,
+    rate_limiter: RateLimiter,
+    retry_policy: RetryPolicy,
+}
+
+impl EtherscanClient {
+    pub fn builder(api_key: impl Into<String>) -> EtherscanClientBuilder {
+        EtherscanClientBuilder {
+            api_key: api_key.into(),
+            base_url: ETHERSCAN_API.to_string(),
+            chain_id: None,
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            requests_per_second: DEFAULT_REQUESTS_PER_SECOND,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Client of another chain of Etherscan v2 API, which shares connection pool and rate limiter
+    /// with this one, as the same api key is used for all chains
+    pub fn for_chain(&self, chain_id: u64) -> Self {
+        Self {
+            chain_id: Some(chain_id),
+            ..self.clone()
+        }
+    }
+
+    /// Sends request for given module and action and deserializes response envelope.
+    /// Transient failures are retried with jittered exponential backoff.
+    async fn get<P: Serialize, R: DeserializeOwned>(
+        &self,
+        module: Module,
+        action: Action,
+        params: P,
+    ) -> Result<R, ServerError> {
+        let request = EtherscanRequest {
+            chainid: self.chain_id,
+            module,
+            action,
+            params,
+            apikey: &self.api_key,
+        };
+
+        let mut attempt = 0;
+
+        let resp = loop {
+            self.rate_limiter.acquire().await;
+
+            match self.send(&request).await {
+                Err(err) if err.is_retryable() && attempt < self.retry_policy.max_retries => {
+                    sleep(self.retry_policy.delay(attempt)).await;
+                    attempt += 1;
+                }
+                resp => break resp?,
+            }
         };
 
-        parsed_transactions.push(parsed_transaction)
-    }
-
-    Ok(parsed_transactions)
-}
-
-pub fn get_fetch_tx_request_string(
-    address: &str,
-    start_block: u64,
-    end_block: u64,
-    page: u32,
-) -> String {
-    let offset = 1000;
-    let sort = "asc";
-
-    ETHERSCAN_API.to_string()
-        + MODULE_ACCOUNT
-        + ACTION_TXLIST
-        + ADDRESS_PLACEHOLDER
-        + address
-        + START_BLOCK_PLACEHOLDER
-        + &start_block.to_string()
-        + END_BLOCK_PLACEHOLDER
-        + &end_block.to_string()
-        + PAGE_PLACEHOLDER
-        + &page.to_string()
-        + OFFSET_PLACEHOLDER
-        + &offset.to_string()
-        + SORT_PLACEHOLDER
-        + sort
-        + API_KEY_PLACEHOLDER
-        + &ETHERSCAN_API_KEY
-}
-
-pub async fn fetch_transactions(
-    (r_start, r_end): (u64, u64),
-    transaction_form_input: &TransactionFormInput,
+        serde_json::from_value(resp).map_err(|e| ServerError::MalformedResponse {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Sends single request. Rate limit replies pause all requests of this client.
+    async fn send<P: Serialize>(
+        &self,
+        request: &EtherscanRequest<'_, P>,
+    ) -> Result<Value, ServerError> {
+        let resp = self
+            .client
+            .get(&self.base_url)
+            .query(request)
+            .send()
+            .await?;
+
+        if resp.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = resp
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|retry_after| retry_after.to_str().ok())
+                .and_then(|retry_after| retry_after.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(RATE_LIMIT_PAUSE);
+            self.rate_limiter.pause(retry_after);
+
+            return Err(ServerError::RateLimited {
+                message: format!("too many requests, retry after {}s", retry_after.as_secs()),
+            });
+        }
+
+        let resp = resp.error_for_status()?.json::<Value>().await?;
+
+        if let Some(message) = rate_limit_message(&resp) {
+            self.rate_limiter.pause(RATE_LIMIT_PAUSE);
+
+            return Err(ServerError::RateLimited {
+                message: message.to_string(),
+            });
+        }
+
+        Ok(resp)
+    }
+
+    /// Fetch all entries of account list action for given address within block range.
+    /// If range doesn't fit into result window, entries of fully fetched blocks are kept
+    /// and fetching continues from the last block, which may have been cut off by the window.
+    async fn fetch_account_list<T: AccountListEntry>(
+        &self,
+        action: Action,
+        address: &str,
+        (mut r_start, r_end): (u64, u64),
+        progress: &dyn FetchProgress,
+    ) -> Result<Vec<T>, ServerError> {
+        let mut entries: Vec<T> = Vec::new();
+
+        loop {
+            let (mut window_entries, complete) = self
+                .fetch_account_list_window(action, address, (r_start, r_end), progress)
+                .await?;
+
+            if complete {
+                entries.extend(window_entries);
+                return Ok(entries);
+            }
+
+            let last_block = match window_entries.last() {
+                Some(last_entry) => parse_field::<u64>(last_entry.block_number(), "blockNumber")?,
+                None => return Err(ServerError::ResultWindowExceeded),
+            };
+
+            // Entries come sorted by block, entries of the last block are fetched again
+            let mut cut_off_entries = 0;
+            for entry in window_entries.iter().rev() {
+                if parse_field::<u64>(entry.block_number(), "blockNumber")? != last_block {
+                    break;
+                }
+                cut_off_entries += 1;
+            }
+
+            // Single block doesn't fit into result window
+            if cut_off_entries == window_entries.len() {
+                return Err(ServerError::ResultWindowExceeded);
+            }
+
+            window_entries.truncate(window_entries.len() - cut_off_entries);
+            entries.extend(window_entries);
+            r_start = last_block;
+        }
+    }
+
+    /// Fetch entries within block range page by page, as long as they fit into result window.
+    /// Returns fetched entries and whether they are all entries of the range.
+    async fn fetch_account_list_window<T: AccountListEntry>(
+        &self,
+        action: Action,
+        address: &str,
+        (r_start, r_end): (u64, u64),
+        progress: &dyn FetchProgress,
+    ) -> Result<(Vec<T>, bool), ServerError> {
+        let mut page = 1;
+        let mut entries: Vec<T> = Vec::new();
+
+        // Fetch entries until there are no more entries left to fetch
+        loop {
+            let request = AccountListRequest {
+                address,
+                start_block: r_start,
+                end_block: r_end,
+                page,
+                offset: TRANSACTIONS_PER_PAGE,
+                sort: Sort::Asc,
+            };
+
+            let resp: EtherscanResponse = self.get(Module::Account, action, request).await?;
+            let page_entries: Vec<T> = match resp.into_result() {
+                Err(ServerError::NoResults) => vec![],
+                Err(ServerError::ResultWindowExceeded) => return Ok((entries, false)),
+                page_entries => page_entries?,
+            };
+            let page_entries_count = page_entries.len() as u64;
+            progress.page_fetched();
+
+            entries.extend(page_entries);
+
+            if page_entries_count < TRANSACTIONS_PER_PAGE {
+                break;
+            }
+
+            // Next page would be refused, there may be more entries left
+            if u64::from(page + 1) * TRANSACTIONS_PER_PAGE > RESULT_WINDOW {
+                return Ok((entries, false));
+            }
+
+            page += 1;
+        }
+
+        Ok((entries, true))
+    }
+
+    /// Fetch all internal transactions of given address within block range
+    pub async fn fetch_internal_transactions(
+        &self,
+        block_range: (u64, u64),
+        transaction_form_input: &TransactionFormInput,
+        progress: &dyn FetchProgress,
+    ) -> Result<Vec<internal_transactions::Model>, ServerError> {
+        let internal_transactions = self
+            .fetch_account_list(
+                Action::TxListInternal,
+                &transaction_form_input.address,
+                block_range,
+                progress,
+            )
+            .await?;
+
+        parse_internal_transactions(internal_transactions, transaction_form_input.chain_id)
+    }
+
+    /// Fetch all ERC-20 token transfers of given address within block range,
+    /// along with metadata of transferred tokens
+    pub async fn fetch_token_transfers(
+        &self,
+        block_range: (u64, u64),
+        transaction_form_input: &TransactionFormInput,
+        progress: &dyn FetchProgress,
+    ) -> Result<(Vec<token_transfers::Model>, Vec<tokens::Model>), ServerError> {
+        let token_transfers = self
+            .fetch_account_list(
+                Action::TokenTx,
+                &transaction_form_input.address,
+                block_range,
+                progress,
+            )
+            .await?;
+
+        parse_token_transfers(token_transfers, transaction_form_input.chain_id)
+    }
+
+    /// Fetch all ERC-721 and ERC-1155 transfers of given address within block range
+    pub async fn fetch_nft_transfers(
+        &self,
+        block_range: (u64, u64),
+        transaction_form_input: &TransactionFormInput,
+        progress: &dyn FetchProgress,
+    ) -> Result<Vec<nft_transfers::Model>, ServerError> {
+        let erc721_transfers = self
+            .fetch_account_list(
+                Action::TokenNftTx,
+                &transaction_form_input.address,
+                block_range,
+                progress,
+            )
+            .await?;
+
+        let erc1155_transfers = self
+            .fetch_account_list(
+                Action::Token1155Tx,
+                &transaction_form_input.address,
+                block_range,
+                progress,
+            )
+            .await?;
+
+        let mut nft_transfers = parse_nft_transfers(
+            erc721_transfers,
+            NftStandard::Erc721,
+            transaction_form_input.chain_id,
+        )?;
+        nft_transfers.extend(parse_nft_transfers(
+            erc1155_transfers,
+            NftStandard::Erc1155,
+            transaction_form_input.chain_id,
+        )?);
+
+        Ok(nft_transfers)
+    }
+}
+
+#[async_trait]
+impl TransactionSource for EtherscanClient {
+    fn name(&self) -> &'static str {
+        "etherscan"
+    }
+
+    /// Get current eth block number
+    async fn get_current_block_number(&self) -> Result<u64, ServerError> {
+        let resp: BlockNumberResponse = self.get(Module::Proxy, Action::EthBlockNumber, ()).await?;
+
+        parse_hex_quantity(&resp.into_result()?, "block number")
+    }
+
+    /// Get hash of block with given number
+    async fn get_block_hash(&self, block_number: u64) -> Result<String, ServerError> {
+        let request = BlockByNumberRequest {
+            tag: format!("{block_number:#x}"),
+            boolean: false,
+        };
+        let resp: BlockResponse = self
+            .get(Module::Proxy, Action::EthGetBlockByNumber, request)
+            .await?;
+
+        Ok(resp.into_result()?.hash)
+    }
+
+    /// Get current balance of given address in wei
+    async fn get_balance(&self, address: &str) -> Result<U256, ServerError> {
+        let request = BalanceRequest {
+            address,
+            tag: "latest",
+        };
+        let resp: EtherscanResponse = self.get(Module::Account, Action::Balance, request).await?;
+        let balance: String = resp.into_result()?;
+
+        parse_field(&balance, "balance")
+    }
+
+    /// Get number of the last block mined before given unix timestamp
+    async fn get_block_number_before(&self, timestamp: i64) -> Result<u64, ServerError> {
+        let request = BlockNumberByTimeRequest {
+            timestamp,
+            closest: Closest::Before,
+        };
+        let resp: EtherscanResponse = self
+            .get(Module::Block, Action::GetBlockNoByTime, request)
+            .await?;
+        let block_number: String = resp.into_result()?;
+
+        parse_field(&block_number, "block number")
+    }
+
+    /// Fetch all transactions of given address within block range
+    async fn fetch_transactions(
+        &self,
+        block_range: (u64, u64),
+        transaction_form_input: &TransactionFormInput,
+        progress: &dyn FetchProgress,
+    ) -> Result<Vec<transactions::Model>, ServerError> {
+        let transactions = self
+            .fetch_account_list(
+                Action::TxList,
+                &transaction_form_input.address,
+                block_range,
+                progress,
+            )
+            .await?;
+
+        parse_transactions(transactions, transaction_form_input.chain_id)
+    }
+}
+
+pub fn parse_transactions(
+    transactions: Vec<EtherscanTransaction>,
+    chain_id: u64,
 ) -> Result<Vec<transactions::Model>, ServerError> {
-    let mut page = 1;
+    transactions
+        .into_iter()
+        .map(|transaction| parse_transaction(transaction, chain_id))
+        .collect()
+}
+
+pub fn parse_transaction(
+    transaction: EtherscanTransaction,
+    chain_id: u64,
+) -> Result<transactions::Model, ServerError> {
+    let value = parse_field::<U256>(&transaction.value, "value")?;
+    let gas_used = parse_field::<u64>(&transaction.gas_used, "gasUsed")?;
+    let gas_price = parse_field::<U256>(&transaction.gas_price, "gasPrice")?;
+    let block_number = parse_field::<u64>(&transaction.block_number, "blockNumber")?;
+    let timestamp = parse_field::<i64>(&transaction.time_stamp, "timeStamp")?;
+
+    let date_time = parse_timestamp(timestamp)?;
+
+    let tx_fee = U256::from(gas_used).checked_mul(gas_price).ok_or_else(|| {
+        ServerError::MalformedResponse {
+            reason: format!("transaction fee overflow: {gas_used} * {gas_price}"),
+        }
+    })?;
+
+    let receipt_status = match transaction.receipt_status.as_str() {
+        "" => None,
+        receipt_status => Some(parse_field::<u8>(receipt_status, "txreceipt_status")? == 1),
+    };
+
+    Ok(transactions::Model {
+        chain_id: chain_id.into(),
+        tx_id: transaction.hash,
+        block_hash: Some(transaction.block_hash).filter(|hash| !hash.is_empty()),
+        address_from: transaction.from,
+        address_to: transaction.to.filter(|to| !to.is_empty()),
+        value,
+        block_number: block_number.into(),
+        date_time,
+        tx_fee,
+        contract_address: Some(transaction.contract_address).filter(|address| !address.is_empty()),
+        is_error: transaction.is_error == "1",
+        receipt_status,
+        nonce: Some(parse_field(&transaction.nonce, "nonce")?),
+        transaction_index: Some(parse_field(
+            &transaction.transaction_index,
+            "transactionIndex",
+        )?),
+        gas: Some(parse_field(&transaction.gas, "gas")?),
+        gas_used: Some(gas_used.into()),
+        gas_price: Some(gas_price),
+        cumulative_gas_used: Some(parse_field(
+            &transaction.cumulative_gas_used,
+            "cumulativeGasUsed",
+        )?),
+        // Older explorer responses don't have method id, it's the selector in call data
+        method_id: Some(if transaction.method_id.is_empty() {
+            method_id(&transaction.input)
+        } else {
+            transaction.method_id
+        }),
+        input: Some(transaction.input),
+        function_name: Some(transaction.function_name).filter(|name| !name.is_empty()),
+        // Etherscan lists lack fee breakdown, it's filled from node if configured
+        tx_type: None,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
+        effective_gas_price: None,
+        base_fee_per_gas: None,
+        blob_gas_used: None,
+        blob_gas_price: None,
+    })
+}
 
-    let request =
-        get_fetch_tx_request_string(&transaction_form_input.address, r_start, r_end, page);
+/// Method selector, i.e. first 4 bytes of call data. `0x` for calls without data.
+pub fn method_id(input: &str) -> String {
+    input.get(..10).unwrap_or("0x").to_string()
+}
 
-    let mut resp = reqwest::get(request).await?.json::<Value>().await?;
+pub fn parse_internal_transactions(
+    internal_transactions: Vec<EtherscanInternalTransaction>,
+    chain_id: u64,
+) -> Result<Vec<internal_transactions::Model>, ServerError> {
+    internal_transactions
+        .into_iter()
+        .map(|internal_transaction| parse_internal_transaction(internal_transaction, chain_id))
+        .collect()
+}
 
-    let mut transactions = resp["result"]
-        .as_array()
-        .ok_or(ServerError::ReqwestParsingError)?;
+pub fn parse_internal_transaction(
+    internal_transaction: EtherscanInternalTransaction,
+    chain_id: u64,
+) -> Result<internal_transactions::Model, ServerError> {
+    let value = parse_field::<U256>(&internal_transaction.value, "value")?;
+    let block_number = parse_field::<u64>(&internal_transaction.block_number, "blockNumber")?;
+    let timestamp = parse_field::<i64>(&internal_transaction.time_stamp, "timeStamp")?;
+
+    // Contract creations have no recipient, value goes to created contract instead
+    let address_to = if internal_transaction.to.is_empty() {
+        internal_transaction.contract_address
+    } else {
+        internal_transaction.to
+    };
+
+    Ok(internal_transactions::Model {
+        chain_id: chain_id.into(),
+        parent_tx_id: internal_transaction.hash,
+        trace_id: internal_transaction.trace_id,
+        block_number: block_number.into(),
+        // Explorer doesn't return it, crawler records hashes of blocks, which are re-checked for reorgs
+        block_hash: None,
+        date_time: parse_timestamp(timestamp)?,
+        address_from: internal_transaction.from,
+        address_to,
+        value,
+        tx_type: internal_transaction.r#type,
+        is_error: internal_transaction.is_error == "1",
+    })
+}
 
-    let mut parsed_transactions: Vec<transactions::Model> = Vec::new();
+/// Parses token transfers and collects metadata of transferred tokens
+pub fn parse_token_transfers(
+    token_transfers: Vec<EtherscanTokenTransfer>,
+    chain_id: u64,
+) -> Result<(Vec<token_transfers::Model>, Vec<tokens::Model>), ServerError> {
+    let mut parsed_token_transfers = vec![];
+    let mut tokens = BTreeMap::new();
+    let mut transfers_per_transaction = HashMap::new();
+
+    for token_transfer in token_transfers {
+        let log_index = parse_log_index(
+            &token_transfer.log_index,
+            &token_transfer.hash,
+            &mut transfers_per_transaction,
+        )?;
+
+        // Some tokens don't implement optional `decimals` method
+        let decimals = if token_transfer.token_decimal.is_empty() {
+            0
+        } else {
+            parse_field::<u32>(&token_transfer.token_decimal, "tokenDecimal")?
+        };
 
-    // Parse transactions until there are no more transactions left to fetch
-    while !transactions.is_empty() {
-        let request =
-            get_fetch_tx_request_string(&transaction_form_input.address, r_start, r_end, page);
+        tokens
+            .entry(token_transfer.contract_address.clone())
+            .or_insert_with(|| tokens::Model {
+                chain_id: chain_id.into(),
+                contract_address: token_transfer.contract_address.clone(),
+                name: token_transfer.token_name.clone(),
+                symbol: token_transfer.token_symbol.clone(),
+                decimals: decimals.into(),
+            });
+
+        let timestamp = parse_field::<i64>(&token_transfer.time_stamp, "timeStamp")?;
+
+        parsed_token_transfers.push(token_transfers::Model {
+            chain_id: chain_id.into(),
+            log_index: log_index.into(),
+            block_number: parse_field(&token_transfer.block_number, "blockNumber")?,
+            block_hash: Some(token_transfer.block_hash).filter(|hash| !hash.is_empty()),
+            date_time: parse_timestamp(timestamp)?,
+            amount: parse_field::<U256>(&token_transfer.value, "value")?,
+            tx_id: token_transfer.hash,
+            contract_address: token_transfer.contract_address,
+            address_from: token_transfer.from,
+            address_to: token_transfer.to,
+        });
+    }
 
-        resp = reqwest::get(request).await?.json::<Value>().await?;
-        transactions = resp["result"]
-            .as_array()
-            .ok_or(ServerError::ReqwestParsingError)?;
+    Ok((parsed_token_transfers, tokens.into_values().collect()))
+}
 
-        parsed_transactions.extend(parse_transactions(transactions).await?);
+pub fn parse_nft_transfers(
+    nft_transfers: Vec<EtherscanNftTransfer>,
+    standard: NftStandard,
+    chain_id: u64,
+) -> Result<Vec<nft_transfers::Model>, ServerError> {
+    let mut parsed_nft_transfers = vec![];
+    let mut transfers_per_transaction = HashMap::new();
+
+    for nft_transfer in nft_transfers {
+        let log_index = parse_log_index(
+            &nft_transfer.log_index,
+            &nft_transfer.hash,
+            &mut transfers_per_transaction,
+        )?;
+
+        let quantity = match &nft_transfer.token_value {
+            Some(token_value) => parse_field::<U256>(token_value, "tokenValue")?,
+            None => U256::from(1),
+        };
 
-        page += 1;
+        let timestamp = parse_field::<i64>(&nft_transfer.time_stamp, "timeStamp")?;
+
+        parsed_nft_transfers.push(nft_transfers::Model {
+            chain_id: chain_id.into(),
+            log_index: log_index.into(),
+            token_id: parse_field::<U256>(&nft_transfer.token_id, "tokenID")?,
+            standard,
+            block_number: parse_field(&nft_transfer.block_number, "blockNumber")?,
+            block_hash: Some(nft_transfer.block_hash).filter(|hash| !hash.is_empty()),
+            date_time: parse_timestamp(timestamp)?,
+            quantity,
+            tx_id: nft_transfer.hash,
+            contract_address: nft_transfer.contract_address,
+            address_from: nft_transfer.from,
+            address_to: nft_transfer.to,
+        });
     }
 
-    Ok(parsed_transactions)
+    Ok(parsed_nft_transfers)
+}
+
+/// Parses log index if explorer returned it, otherwise falls back to
+/// the number of transfers seen so far in the same transaction
+fn parse_log_index(
+    log_index: &Option<String>,
+    tx_hash: &str,
+    transfers_per_transaction: &mut HashMap<String, u64>,
+) -> Result<u64, ServerError> {
+    let transfer_ordinal = transfers_per_transaction
+        .entry(tx_hash.to_string())
+        .or_default();
+
+    let log_index = match log_index {
+        Some(log_index) => parse_field::<u64>(log_index, "logIndex")?,
+        None => *transfer_ordinal,
+    };
+    *transfer_ordinal += 1;
+
+    Ok(log_index)
+}
+
+fn parse_timestamp(timestamp: i64) -> Result<chrono::NaiveDateTime, ServerError> {
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .map(|date_time| date_time.naive_utc())
+        .ok_or_else(|| ServerError::MalformedResponse {
+            reason: format!("invalid timeStamp: {timestamp}"),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn method_id_is_selector_of_call_data() {
+        assert_eq!(
+            method_id("0xa9059cbb000000000000000000000000000000000000000000000000000000000000dead"),
+            "0xa9059cbb"
+        );
+        assert_eq!(method_id("0xa9059cbb"), "0xa9059cbb");
+        // plain transfers and too short call data
+        assert_eq!(method_id("0x"), "0x");
+        assert_eq!(method_id(""), "0x");
+        assert_eq!(method_id("0xa9059c"), "0x");
+    }
 }
@@ -0,0 +1,225 @@
+use crate::{errors::ServerError, helpers::*};
+use actix_example_service::{
+    sea_orm::DatabaseConnection, Query, TransactionFilter, TransactionKey, TransactionWithFees,
+};
+use actix_web::web::Bytes;
+use clap::ValueEnum;
+use entity::u256::U256;
+use futures::{stream, Stream, StreamExt};
+use serde::Deserialize;
+
+// Number of transactions loaded from database at once
+pub const EXPORT_CHUNK_SIZE: u64 = 1000;
+
+const GWEI_DECIMALS: usize = 9;
+
+const CSV_HEADER: [&str; 20] = [
+    "tx_id",
+    "block_number",
+    "transaction_index",
+    "date_time",
+    "address_from",
+    "address_to",
+    "contract_address",
+    "value",
+    "tx_fee",
+    "burnt_fee",
+    "priority_fee",
+    "gas",
+    "gas_used",
+    "gas_price",
+    "nonce",
+    "tx_type",
+    "status",
+    "method_id",
+    "function_name",
+    "input",
+];
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Ndjson,
+}
+
+/// Units of amounts in CSV export. NDJSON amounts are always in wei.
+#[derive(Debug, Clone, Copy, Default, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    Wei,
+    Gwei,
+    #[default]
+    Eth,
+}
+
+impl Units {
+    fn format(self, amount: &U256) -> String {
+        match self {
+            Self::Wei => amount.to_string(),
+            Self::Gwei => format_amount(&amount.to_string(), GWEI_DECIMALS),
+            Self::Eth => format_amount(&amount.to_string(), ETHER_DECIMALS),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// Streams transactions of address on chain, which match filter, chunk by chunk.
+/// CSV header is sent first, even if there are no transactions.
+pub fn export_transactions(
+    conn: DatabaseConnection,
+    chain_id: u64,
+    address: String,
+    filter: TransactionFilter,
+    format: ExportFormat,
+    units: Units,
+) -> impl Stream<Item = Result<Bytes, ServerError>> {
+    let header = match format {
+        ExportFormat::Csv => Some(encode_csv_header()),
+        ExportFormat::Ndjson => None,
+    };
+
+    // state is the key of the last exported transaction, or None once all are exported
+    let rows = stream::try_unfold(Some(None), move |after: Option<Option<TransactionKey>>| {
+        let (conn, address, filter) = (conn.clone(), address.clone(), filter.clone());
+
+        async move {
+            let Some(after) = after else {
+                return Ok(None);
+            };
+
+            let transactions = Query::find_filtered_transactions_chunk(
+                &conn,
+                chain_id,
+                &address,
+                &filter,
+                after.as_ref(),
+                EXPORT_CHUNK_SIZE,
+            )
+            .await
+            .map_err(ServerError::from)?;
+
+            if transactions.is_empty() {
+                return Ok(None);
+            }
+
+            let next_chunk = (transactions.len() as u64 == EXPORT_CHUNK_SIZE).then(|| {
+                transactions
+                    .last()
+                    .map(|last| TransactionKey::from(&last.transaction))
+            });
+
+            let bytes = match format {
+                ExportFormat::Csv => encode_csv_rows(&transactions, units)?,
+                ExportFormat::Ndjson => encode_ndjson_rows(&transactions)?,
+            };
+
+            Ok(Some((bytes, next_chunk)))
+        }
+    });
+
+    stream::iter(header).chain(rows)
+}
+
+fn encode_csv_header() -> Result<Bytes, ServerError> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(CSV_HEADER).map_err(csv_error)?;
+
+    csv_bytes(writer)
+}
+
+fn encode_csv_rows(
+    transactions: &[TransactionWithFees],
+    units: Units,
+) -> Result<Bytes, ServerError> {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    let mut writer = csv::Writer::from_writer(vec![]);
+
+    for TransactionWithFees {
+        transaction,
+        burnt_fee,
+        priority_fee,
+    } in transactions
+    {
+        let status = if transaction.is_failed() {
+            "failed"
+        } else {
+            "succeeded"
+        };
+
+        writer
+            .write_record([
+                transaction.tx_id.clone(),
+                transaction.block_number.to_string(),
+                optional(transaction.transaction_index.map(|index| index.to_string())),
+                transaction.date_time.to_string(),
+                transaction.address_from.clone(),
+                optional(transaction.address_to.clone()),
+                optional(transaction.contract_address.clone()),
+                units.format(&transaction.value),
+                units.format(&transaction.tx_fee),
+                optional(burnt_fee.map(|burnt_fee| units.format(&burnt_fee))),
+                optional(priority_fee.map(|priority_fee| units.format(&priority_fee))),
+                optional(transaction.gas.map(|gas| gas.to_string())),
+                optional(transaction.gas_used.map(|gas_used| gas_used.to_string())),
+                optional(
+                    transaction
+                        .gas_price
+                        .map(|gas_price| units.format(&gas_price)),
+                ),
+                optional(transaction.nonce.map(|nonce| nonce.to_string())),
+                optional(transaction.tx_type.map(|tx_type| tx_type.to_string())),
+                status.to_string(),
+                optional(transaction.method_id.clone()),
+                optional(transaction.function_name.clone()),
+                optional(transaction.input.clone()),
+            ])
+            .map_err(csv_error)?;
+    }
+
+    csv_bytes(writer)
+}
+
+fn encode_ndjson_rows(transactions: &[TransactionWithFees]) -> Result<Bytes, ServerError> {
+    let mut bytes = vec![];
+
+    for transaction in transactions {
+        serde_json::to_writer(&mut bytes, transaction).map_err(|e| ServerError::ExportError {
+            message: e.to_string(),
+        })?;
+        bytes.push(b'\n');
+    }
+
+    Ok(Bytes::from(bytes))
+}
+
+fn csv_bytes(writer: csv::Writer<Vec<u8>>) -> Result<Bytes, ServerError> {
+    writer
+        .into_inner()
+        .map(Bytes::from)
+        .map_err(|e| ServerError::ExportError {
+            message: e.to_string(),
+        })
+}
+
+fn csv_error(error: csv::Error) -> ServerError {
+    ServerError::ExportError {
+        message: error.to_string(),
+    }
+}
@@ -1,50 +1,42 @@
-use std::collections::BTreeSet;
-
-// Retrieves block ranges for unfetched transactions
-pub fn get_block_ranges_for_unfetched_transactions(
-    fetched_block_numbers_since_block: BTreeSet<u64>,
-    starting_block_number: u64,
-    current_block_number: u64,
-) -> Vec<(u64, u64)> {
-    let all_block_numbers_since_block: BTreeSet<u64> =
-        (starting_block_number..current_block_number).collect();
-
-    // I assume it should be sorted in ascending order here
-    let unfetched_block_numbers_since_block: Vec<u64> = all_block_numbers_since_block
-        .difference(&fetched_block_numbers_since_block)
-        .cloned()
-        .collect();
-
-    let mut ranges = vec![];
-
-    let mut start = unfetched_block_numbers_since_block[0];
-    for i in 1..unfetched_block_numbers_since_block.len() {
-        let value = unfetched_block_numbers_since_block[i];
-        let previous = unfetched_block_numbers_since_block[i - 1];
-
-        if value == previous + 1 {
-            continue;
-        }
-
-        if start == previous {
-            ranges.push((previous, previous));
-        } else {
-            ranges.push((start, previous));
-        }
-        start = value;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Number of decimals of ether
+pub const ETHER_DECIMALS: usize = 18;
+
+/// Formats integer amount, given in smallest units (e.g. wei), with given number of decimals
+pub fn format_amount(amount: &str, decimals: usize) -> String {
+    let digits = amount.trim_start_matches('0');
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (integer, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        integer.to_string() + "." + fraction
     }
+}
 
-    // Save last range
-    if let Some((_, last)) = ranges.last().cloned() {
-        ranges.push((last + 2, current_block_number))
-    }
+/// Tera filter, formats amount with `decimals` argument, ether decimals are used by default
+pub fn format_units(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let amount = match value {
+        Value::String(amount) => amount.clone(),
+        Value::Number(amount) if amount.is_u64() => amount.to_string(),
+        _ => {
+            return Err(tera::Error::msg(
+                "format_units expects unsigned integer amount",
+            ))
+        }
+    };
 
-    if ranges.is_empty() {
-        ranges.push((
-            unfetched_block_numbers_since_block[0],
-            unfetched_block_numbers_since_block[unfetched_block_numbers_since_block.len() - 1],
-        ));
-    }
+    let decimals = match args.get("decimals") {
+        Some(decimals) => decimals
+            .as_u64()
+            .ok_or_else(|| tera::Error::msg("format_units expects unsigned integer decimals"))?
+            as usize,
+        None => ETHER_DECIMALS,
+    };
 
-    ranges
+    Ok(Value::String(format_amount(&amount, decimals)))
 }
@@ -0,0 +1,332 @@
+use crate::{
+    chains::{Chain, Chains},
+    crawler::*,
+    errors::ServerError,
+    TransactionFormInput,
+};
+use actix_example_service::{sea_orm::DatabaseConnection, Mutation, Query};
+use entity::{crawl_coverage::CoverageSource, crawl_jobs::CrawlJobStatus, watched_addresses};
+use futures::{channel::mpsc, StreamExt};
+use std::{collections::HashMap, time::Duration};
+
+// Default interval between scheduled crawls of watched addresses
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Hands crawl jobs over to background worker
+#[derive(Debug, Clone)]
+pub struct CrawlJobQueue {
+    sender: mpsc::UnboundedSender<i32>,
+}
+
+impl CrawlJobQueue {
+    pub fn enqueue(&self, job_id: i32) -> Result<(), ServerError> {
+        self.sender
+            .unbounded_send(job_id)
+            .map_err(|_| ServerError::CrawlQueueClosed)
+    }
+}
+
+/// Spawns worker on current actix runtime, which runs queued crawl jobs one by one.
+/// Jobs left unfinished by previous run are queued again.
+pub async fn spawn_crawl_worker(
+    conn: DatabaseConnection,
+    chains: Chains,
+    confirmation_depth: u64,
+    reorg_recheck_blocks: u64,
+) -> Result<CrawlJobQueue, ServerError> {
+    let (sender, mut receiver) = mpsc::unbounded();
+    let queue = CrawlJobQueue { sender };
+
+    for job in Query::find_unfinished_crawl_jobs(&conn)
+        .await
+        .map_err(ServerError::from)?
+    {
+        queue.enqueue(job.id)?;
+    }
+
+    actix_web::rt::spawn(async move {
+        while let Some(job_id) = receiver.next().await {
+            if let Err(err) = run_crawl_job(
+                &conn,
+                &chains,
+                confirmation_depth,
+                reorg_recheck_blocks,
+                job_id,
+            )
+            .await
+            {
+                println!("Crawl job {job_id} failed: {err}");
+            }
+        }
+    });
+
+    Ok(queue)
+}
+
+/// Runs crawl job with clients of its chain and records its outcome.
+/// Crawl error is returned once the job is marked failed.
+pub async fn run_crawl_job(
+    conn: &DatabaseConnection,
+    chains: &Chains,
+    confirmation_depth: u64,
+    reorg_recheck_blocks: u64,
+    job_id: i32,
+) -> Result<(), ServerError> {
+    let job = Query::find_crawl_job_by_id(conn, job_id)
+        .await
+        .map_err(ServerError::from)?
+        .ok_or(ServerError::CrawlJobNotFound { id: job_id })?;
+
+    Mutation::update_crawl_job_status(conn, job.id, CrawlJobStatus::Running, None)
+        .await
+        .map_err(ServerError::from)?;
+
+    let form = TransactionFormInput {
+        chain_id: job.chain_id.0,
+        address: job.address,
+        starting_block_number: job.starting_block_number.0,
+        ending_block_number: job.ending_block_number.map(u64::from),
+    };
+    let progress = CrawlProgress::new(job.id);
+
+    // Chain may have been removed from config since the job was created
+    let result = match chains.get(form.chain_id) {
+        Ok(chain) => {
+            crawl(
+                conn,
+                chain.source.as_ref(),
+                chain.etherscan.as_ref(),
+                chain.json_rpc.as_ref(),
+                &form,
+                confirmation_depth,
+                reorg_recheck_blocks,
+                &progress,
+            )
+            .await
+        }
+        Err(err) => Err(err),
+    };
+
+    let (status, error) = match &result {
+        Ok(()) => (CrawlJobStatus::Completed, None),
+        Err(err) => (CrawlJobStatus::Failed, Some(err.to_string())),
+    };
+
+    Mutation::update_crawl_job_status(conn, job.id, status, error)
+        .await
+        .map_err(ServerError::from)?;
+
+    result
+}
+
+/// Spawns scheduler on current actix runtime, which runs on given interval.
+/// Every run re-checks `recheck_blocks` latest blocks of every chain for reorgs and crawls orphaned blocks again,
+/// then queues crawl of every active watched address, which isn't covered up to the last block,
+/// which has `confirmation_depth` blocks on top of it. Crawls fetch only blocks, which are not covered yet.
+/// Reorg re-check is disabled if `recheck_blocks` is zero.
+pub fn spawn_scheduler(
+    conn: DatabaseConnection,
+    chains: Chains,
+    crawl_jobs: CrawlJobQueue,
+    watch_interval: Duration,
+    confirmation_depth: u64,
+    recheck_blocks: u64,
+) {
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(watch_interval);
+
+        loop {
+            interval.tick().await;
+
+            if recheck_blocks > 0 {
+                for chain in chains.iter() {
+                    if let Err(err) =
+                        schedule_reorged_block_crawls(&conn, chain, &crawl_jobs, recheck_blocks)
+                            .await
+                    {
+                        println!(
+                            "Failed to re-check recent blocks of chain {} for reorgs: {err}",
+                            chain.name
+                        );
+                    }
+                }
+            }
+
+            if let Err(err) =
+                schedule_watched_address_crawls(&conn, &chains, &crawl_jobs, confirmation_depth)
+                    .await
+            {
+                println!("Failed to schedule crawls of watched addresses: {err}");
+            }
+        }
+    });
+}
+
+/// Deletes rows of reorged blocks of chain and queues crawl jobs, which fetch them again
+async fn schedule_reorged_block_crawls(
+    conn: &DatabaseConnection,
+    chain: &Chain,
+    crawl_jobs: &CrawlJobQueue,
+    recheck_blocks: u64,
+) -> Result<(), ServerError> {
+    for (address, first_orphaned_block) in
+        recheck_recent_blocks(conn, chain.chain_id, chain.source.as_ref(), recheck_blocks).await?
+    {
+        let job =
+            Mutation::create_crawl_job(conn, chain.chain_id, address, first_orphaned_block, None)
+                .await
+                .map_err(ServerError::from)?;
+        crawl_jobs.enqueue(job.id)?;
+    }
+
+    Ok(())
+}
+
+/// Queues crawl job for every active watched address, which isn't being crawled already
+/// and isn't covered up to the last confirmed block yet. Addresses of chains, which are
+/// no longer configured, are skipped. Failure to schedule one address doesn't stop the others.
+async fn schedule_watched_address_crawls(
+    conn: &DatabaseConnection,
+    chains: &Chains,
+    crawl_jobs: &CrawlJobQueue,
+    confirmation_depth: u64,
+) -> Result<(), ServerError> {
+    // Current block is fetched once per chain, None if fetching it failed
+    let mut last_block_numbers: HashMap<u64, Option<u64>> = HashMap::new();
+
+    for watched_address in Query::find_active_watched_addresses(conn)
+        .await
+        .map_err(ServerError::from)?
+    {
+        let Ok(chain) = chains.get(watched_address.chain_id.0) else {
+            continue;
+        };
+
+        let last_block_number = match last_block_numbers.get(&chain.chain_id) {
+            Some(last_block_number) => *last_block_number,
+            None => {
+                let last_block_number = match chain.source.get_current_block_number().await {
+                    Ok(current_block_number) => {
+                        Some(current_block_number.saturating_sub(confirmation_depth))
+                    }
+                    Err(err) => {
+                        println!("Failed to get current block of chain {}: {err}", chain.name);
+                        None
+                    }
+                };
+                last_block_numbers.insert(chain.chain_id, last_block_number);
+                last_block_number
+            }
+        };
+        let Some(last_block_number) = last_block_number else {
+            continue;
+        };
+
+        if let Err(err) = schedule_watched_address_crawl(
+            conn,
+            chain,
+            crawl_jobs,
+            &watched_address,
+            last_block_number,
+        )
+        .await
+        {
+            println!(
+                "Failed to schedule crawl of watched address {} on chain {}: {err}",
+                watched_address.address, chain.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Queues crawl job for watched address, unless it's crawled already or covered up to last block.
+/// Previous job of the address is deleted once it has completed, so that jobs don't pile up.
+async fn schedule_watched_address_crawl(
+    conn: &DatabaseConnection,
+    chain: &Chain,
+    crawl_jobs: &CrawlJobQueue,
+    watched_address: &watched_addresses::Model,
+    last_block_number: u64,
+) -> Result<(), ServerError> {
+    let active_crawl_job = Query::find_active_crawl_job_for_selected_address(
+        conn,
+        chain.chain_id,
+        &watched_address.address,
+    )
+    .await
+    .map_err(ServerError::from)?;
+
+    if active_crawl_job.is_some()
+        || is_covered_up_to(conn, chain, watched_address, last_block_number).await?
+    {
+        return Ok(());
+    }
+
+    let job = Mutation::create_crawl_job(
+        conn,
+        chain.chain_id,
+        watched_address.address.clone(),
+        watched_address.starting_block_number.0,
+        None,
+    )
+    .await
+    .map_err(ServerError::from)?;
+    crawl_jobs.enqueue(job.id)?;
+
+    Mutation::record_watched_address_crawl(conn, watched_address.id, job.id)
+        .await
+        .map_err(ServerError::from)?;
+
+    // Failed jobs are kept, so that their errors can be looked at
+    if let Some(previous_job_id) = watched_address.last_crawl_job_id {
+        Mutation::delete_completed_crawl_job(conn, previous_job_id)
+            .await
+            .map_err(ServerError::from)?;
+    }
+
+    Ok(())
+}
+
+/// True if every crawl pass, which runs on chain, covers watched address up to last block
+async fn is_covered_up_to(
+    conn: &DatabaseConnection,
+    chain: &Chain,
+    watched_address: &watched_addresses::Model,
+    last_block_number: u64,
+) -> Result<bool, ServerError> {
+    let starting_block_number = watched_address.starting_block_number.0;
+    if starting_block_number > last_block_number {
+        return Ok(true);
+    }
+
+    let sources: &[CoverageSource] = if chain.etherscan.is_some() {
+        &[
+            CoverageSource::Transactions,
+            CoverageSource::InternalTransactions,
+            CoverageSource::TokenTransfers,
+            CoverageSource::NftTransfers,
+        ]
+    } else {
+        &[CoverageSource::Transactions]
+    };
+
+    for source in sources {
+        let uncovered_block_ranges = Query::find_uncovered_block_ranges(
+            conn,
+            chain.chain_id,
+            &watched_address.address,
+            *source,
+            (starting_block_number, last_block_number),
+        )
+        .await
+        .map_err(ServerError::from)?;
+
+        if !uncovered_block_ranges.is_empty() {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
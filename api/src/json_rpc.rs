@@ -0,0 +1,430 @@
+use crate::{
+    errors::*,
+    eth_api::method_id,
+    responses::*,
+    transaction_source::{FetchProgress, TransactionSource},
+    TransactionFormInput,
+};
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use entity::{
+    transactions,
+    u256::U256,
+    unsigned::{U64, U8},
+};
+use futures::future::try_join_all;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+// Default timeout
+pub const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Number of blocks requested concurrently
+pub const BLOCKS_PER_BATCH: u64 = 16;
+
+// Number of transactions, whose fee breakdown is requested concurrently
+pub const TRANSACTIONS_PER_BATCH: usize = 16;
+
+/// EIP-1559 and EIP-4844 fee breakdown of single transaction
+#[derive(Debug, Clone, Default)]
+pub struct FeeDetails {
+    pub tx_type: Option<u8>,
+    pub max_fee_per_gas: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub effective_gas_price: U256,
+    pub base_fee_per_gas: Option<U256>,
+    pub blob_gas_used: Option<u64>,
+    pub blob_gas_price: Option<U256>,
+}
+
+impl FeeDetails {
+    pub fn parse(
+        transaction: &RpcTransaction,
+        receipt: &RpcReceipt,
+        base_fee_per_gas: Option<&str>,
+    ) -> Result<Self, ServerError> {
+        let parse_optional_u256 = |value: Option<&str>, field| {
+            value.map(|value| parse_hex_u256(value, field)).transpose()
+        };
+
+        // Receipts before London fork have no effective gas price, gas price was paid in full
+        let effective_gas_price = parse_optional_u256(
+            receipt
+                .effective_gas_price
+                .as_deref()
+                .or(transaction.gas_price.as_deref()),
+            "effectiveGasPrice",
+        )?
+        .unwrap_or_default();
+
+        Ok(Self {
+            tx_type: transaction
+                .r#type
+                .as_deref()
+                .map(|tx_type| parse_hex_quantity(tx_type, "type"))
+                .transpose()?
+                .map(|tx_type| tx_type as u8),
+            max_fee_per_gas: parse_optional_u256(
+                transaction.max_fee_per_gas.as_deref(),
+                "maxFeePerGas",
+            )?,
+            max_priority_fee_per_gas: parse_optional_u256(
+                transaction.max_priority_fee_per_gas.as_deref(),
+                "maxPriorityFeePerGas",
+            )?,
+            effective_gas_price,
+            base_fee_per_gas: parse_optional_u256(base_fee_per_gas, "baseFeePerGas")?,
+            blob_gas_used: receipt
+                .blob_gas_used
+                .as_deref()
+                .map(|blob_gas_used| parse_hex_quantity(blob_gas_used, "blobGasUsed"))
+                .transpose()?,
+            blob_gas_price: parse_optional_u256(receipt.blob_gas_price.as_deref(), "blobGasPrice")?,
+        })
+    }
+
+    /// Stores fee breakdown in transaction. Blob fee is charged on top of gas fee,
+    /// so it's added to transaction fee.
+    pub fn apply(self, transaction: &mut transactions::Model) -> Result<(), ServerError> {
+        let blob_fee = U256::from(self.blob_gas_used.unwrap_or_default())
+            .checked_mul(self.blob_gas_price.unwrap_or_default())
+            .and_then(|blob_fee| transaction.tx_fee.checked_add(blob_fee));
+
+        transaction.tx_fee = blob_fee.ok_or_else(|| ServerError::MalformedResponse {
+            reason: format!("blob fee overflow in transaction {}", transaction.tx_id),
+        })?;
+        transaction.tx_type = self.tx_type.map(U8);
+        transaction.max_fee_per_gas = self.max_fee_per_gas;
+        transaction.max_priority_fee_per_gas = self.max_priority_fee_per_gas;
+        transaction.effective_gas_price = Some(self.effective_gas_price);
+        transaction.base_fee_per_gas = self.base_fee_per_gas;
+        transaction.blob_gas_used = self.blob_gas_used.map(U64);
+        transaction.blob_gas_price = self.blob_gas_price;
+
+        Ok(())
+    }
+}
+
+/// Ethereum JSON-RPC client, scans blocks for transactions of an address.
+/// Works with any node, e.g. own archive node or local devnet such as anvil.
+#[derive(Debug, Clone)]
+pub struct JsonRpcClient {
+    client: reqwest::Client,
+    url: String,
+    next_id: Arc<AtomicU64>,
+}
+
+impl JsonRpcClient {
+    pub fn new(url: impl Into<String>, timeout: Duration) -> Result<Self, ServerError> {
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+
+        Ok(Self {
+            client,
+            url: url.into(),
+            next_id: Arc::new(AtomicU64::new(1)),
+        })
+    }
+
+    /// Calls JSON-RPC method and deserializes its result
+    async fn call<P: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R, ServerError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+
+        let resp = self
+            .client
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<serde_json::Value>()
+            .await?;
+
+        let resp: JsonRpcResponse<R> =
+            serde_json::from_value(resp).map_err(|e| ServerError::MalformedResponse {
+                reason: e.to_string(),
+            })?;
+
+        resp.into_result()
+    }
+
+    /// Get block header, transactions are returned as hashes
+    pub async fn get_block(&self, block_number: u64) -> Result<RpcBlock<String>, ServerError> {
+        self.call(
+            "eth_getBlockByNumber",
+            (format!("{block_number:#x}"), false),
+        )
+        .await
+    }
+
+    /// Get block along with full transaction objects
+    pub async fn get_block_with_transactions(
+        &self,
+        block_number: u64,
+    ) -> Result<RpcBlock<RpcTransaction>, ServerError> {
+        self.call("eth_getBlockByNumber", (format!("{block_number:#x}"), true))
+            .await
+    }
+
+    pub async fn get_transaction(&self, tx_hash: &str) -> Result<RpcTransaction, ServerError> {
+        self.call("eth_getTransactionByHash", [tx_hash]).await
+    }
+
+    pub async fn get_transaction_receipt(&self, tx_hash: &str) -> Result<RpcReceipt, ServerError> {
+        self.call("eth_getTransactionReceipt", [tx_hash]).await
+    }
+
+    /// Fills fee breakdown of transactions, fetched from a source which lacks it, e.g. Etherscan.
+    /// Transactions, which have it already, are skipped. Base fee is fetched once per block.
+    pub async fn fill_fee_details(
+        &self,
+        transactions: &mut [transactions::Model],
+    ) -> Result<(), ServerError> {
+        let mut transactions_without_fee_details: Vec<_> = transactions
+            .iter_mut()
+            .filter(|transaction| transaction.effective_gas_price.is_none())
+            .collect();
+
+        let block_numbers: Vec<u64> = transactions_without_fee_details
+            .iter()
+            .map(|transaction| transaction.block_number.0)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let mut base_fees: HashMap<u64, Option<String>> = HashMap::new();
+
+        for batch in block_numbers.chunks(BLOCKS_PER_BATCH as usize) {
+            let blocks = try_join_all(
+                batch
+                    .iter()
+                    .map(|block_number| self.get_block(*block_number)),
+            )
+            .await?;
+
+            base_fees.extend(
+                batch
+                    .iter()
+                    .zip(blocks)
+                    .map(|(block_number, block)| (*block_number, block.base_fee_per_gas)),
+            );
+        }
+
+        for batch in transactions_without_fee_details.chunks_mut(TRANSACTIONS_PER_BATCH) {
+            let fee_details = try_join_all(batch.iter().map(|transaction| {
+                let base_fee_per_gas = base_fees
+                    .get(&transaction.block_number.0)
+                    .and_then(Option::as_deref);
+
+                self.get_fee_details(&transaction.tx_id, base_fee_per_gas)
+            }))
+            .await?;
+
+            for (transaction, fee_details) in batch.iter_mut().zip(fee_details) {
+                fee_details.apply(transaction)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_fee_details(
+        &self,
+        tx_hash: &str,
+        base_fee_per_gas: Option<&str>,
+    ) -> Result<FeeDetails, ServerError> {
+        let transaction = self.get_transaction(tx_hash).await?;
+        let receipt = self.get_transaction_receipt(tx_hash).await?;
+
+        FeeDetails::parse(&transaction, &receipt, base_fee_per_gas)
+    }
+
+    /// Get transactions of crawled address in single block
+    async fn fetch_block_transactions(
+        &self,
+        block_number: u64,
+        transaction_form_input: &TransactionFormInput,
+    ) -> Result<Vec<transactions::Model>, ServerError> {
+        let address = &transaction_form_input.address;
+        let block = self.get_block_with_transactions(block_number).await?;
+        let timestamp = parse_hex_quantity(&block.timestamp, "timestamp")?;
+
+        let date_time = Utc
+            .timestamp_opt(timestamp as i64, 0)
+            .single()
+            .ok_or_else(|| ServerError::MalformedResponse {
+                reason: format!("invalid timestamp: {timestamp}"),
+            })?
+            .naive_utc();
+
+        let mut parsed_transactions = vec![];
+
+        for transaction in block.transactions {
+            let is_sender = transaction.from.eq_ignore_ascii_case(address);
+            let is_recipient = transaction
+                .to
+                .as_deref()
+                .is_some_and(|to| to.eq_ignore_ascii_case(address));
+
+            if !is_sender && !is_recipient {
+                continue;
+            }
+
+            let receipt = self.get_transaction_receipt(&transaction.hash).await?;
+
+            let gas_used = parse_hex_quantity(&receipt.gas_used, "gasUsed")?;
+            let gas_price = match receipt
+                .effective_gas_price
+                .as_deref()
+                .or(transaction.gas_price.as_deref())
+            {
+                Some(gas_price) => parse_hex_u256(gas_price, "gasPrice")?,
+                None => U256::zero(),
+            };
+
+            let tx_fee = U256::from(gas_used).checked_mul(gas_price).ok_or_else(|| {
+                ServerError::MalformedResponse {
+                    reason: format!("transaction fee overflow: {gas_used} * {gas_price}"),
+                }
+            })?;
+
+            let fee_details =
+                FeeDetails::parse(&transaction, &receipt, block.base_fee_per_gas.as_deref())?;
+
+            let receipt_status = receipt
+                .status
+                .as_deref()
+                .map(|status| parse_hex_quantity(status, "status"))
+                .transpose()?
+                .map(|status| status == 1);
+
+            let mut parsed_transaction = transactions::Model {
+                chain_id: transaction_form_input.chain_id.into(),
+                tx_id: transaction.hash,
+                block_number: block_number.into(),
+                block_hash: Some(block.hash.clone()),
+                date_time,
+                address_from: transaction.from,
+                address_to: transaction.to,
+                value: parse_hex_u256(&transaction.value, "value")?,
+                tx_fee,
+                contract_address: receipt.contract_address,
+                // Nodes report failures through receipt status only
+                is_error: receipt_status == Some(false),
+                receipt_status,
+                nonce: Some(parse_hex_quantity(&transaction.nonce, "nonce")?.into()),
+                transaction_index: Some(
+                    parse_hex_quantity(&transaction.transaction_index, "transactionIndex")?.into(),
+                ),
+                gas: Some(parse_hex_quantity(&transaction.gas, "gas")?.into()),
+                gas_used: Some(gas_used.into()),
+                gas_price: Some(gas_price),
+                cumulative_gas_used: Some(
+                    parse_hex_quantity(&receipt.cumulative_gas_used, "cumulativeGasUsed")?.into(),
+                ),
+                method_id: Some(method_id(&transaction.input)),
+                input: Some(transaction.input),
+                // Node doesn't know method signatures
+                function_name: None,
+                // Filled from fee breakdown below
+                tx_type: None,
+                max_fee_per_gas: None,
+                max_priority_fee_per_gas: None,
+                effective_gas_price: None,
+                base_fee_per_gas: None,
+                blob_gas_used: None,
+                blob_gas_price: None,
+            };
+            fee_details.apply(&mut parsed_transaction)?;
+
+            parsed_transactions.push(parsed_transaction);
+        }
+
+        Ok(parsed_transactions)
+    }
+}
+
+#[async_trait]
+impl TransactionSource for JsonRpcClient {
+    fn name(&self) -> &'static str {
+        "json_rpc"
+    }
+
+    async fn get_current_block_number(&self) -> Result<u64, ServerError> {
+        let block_number: String = self.call("eth_blockNumber", ()).await?;
+
+        parse_hex_quantity(&block_number, "block number")
+    }
+
+    async fn get_block_hash(&self, block_number: u64) -> Result<String, ServerError> {
+        Ok(self.get_block(block_number).await?.hash)
+    }
+
+    async fn get_balance(&self, address: &str) -> Result<U256, ServerError> {
+        let balance: String = self.call("eth_getBalance", (address, "latest")).await?;
+
+        parse_hex_u256(&balance, "balance")
+    }
+
+    /// Binary search over block timestamps
+    async fn get_block_number_before(&self, timestamp: i64) -> Result<u64, ServerError> {
+        let timestamp = timestamp.max(0) as u64;
+        let (mut low, mut high) = (0, self.get_current_block_number().await?);
+
+        while low < high {
+            let middle = low + (high - low).div_ceil(2);
+            let block = self.get_block(middle).await?;
+
+            if parse_hex_quantity(&block.timestamp, "timestamp")? <= timestamp {
+                low = middle;
+            } else {
+                high = middle - 1;
+            }
+        }
+
+        Ok(low)
+    }
+
+    /// Scans every block in range, blocks are requested in concurrent batches.
+    /// Every batch is reported as a fetched page.
+    async fn fetch_transactions(
+        &self,
+        (r_start, r_end): (u64, u64),
+        transaction_form_input: &TransactionFormInput,
+        progress: &dyn FetchProgress,
+    ) -> Result<Vec<transactions::Model>, ServerError> {
+        let mut parsed_transactions = vec![];
+        let mut batch_start = r_start;
+
+        while batch_start <= r_end {
+            let batch_end = r_end.min(batch_start + BLOCKS_PER_BATCH - 1);
+
+            let blocks_transactions = try_join_all((batch_start..=batch_end).map(|block_number| {
+                self.fetch_block_transactions(block_number, transaction_form_input)
+            }))
+            .await?;
+            progress.page_fetched();
+
+            parsed_transactions.extend(blocks_transactions.into_iter().flatten());
+
+            batch_start = batch_end + 1;
+        }
+
+        Ok(parsed_transactions)
+    }
+}
@@ -1,46 +1,175 @@
-#[macro_use]
-extern crate lazy_static;
-
+mod api_v1;
+pub mod chains;
+mod cli;
+pub mod config;
+mod crawler;
 pub mod errors;
-mod eth_api;
+pub mod eth_api;
+mod export;
 mod helpers;
+mod jobs;
+pub mod json_rpc;
+pub mod rate_limit;
+pub mod responses;
+pub mod transaction_source;
 pub mod validators;
 
 use actix_example_service::{
-    sea_orm::{Database, DatabaseConnection},
-    Mutation, Query,
+    merge_block_ranges,
+    sea_orm::{ConnectOptions, Database, DatabaseConnection},
+    BlockOrDate, Mutation, Query, TransactionFilter, TransactionSort,
 };
 use actix_files::Files as Fs;
 use actix_web::{
     error, get, middleware, post, web, App, Error, HttpRequest, HttpResponse, HttpServer, Result,
 };
 
-use crate::errors::ServerError;
-use eth_api::*;
+use crate::{
+    chains::Chains,
+    config::{Config, DatabaseConfig, PagesConfig},
+    errors::ServerError,
+};
+use entity::{crawl_coverage::CoverageSource, u256::U256, watched_addresses::WatchStatus};
 use helpers::*;
+use jobs::*;
 use listenfd::ListenFd;
 use migration::{Migrator, MigratorTrait};
 use serde::{Deserialize, Serialize};
-use std::env;
+use std::{io, process::ExitCode, time::Duration};
 use tera::Tera;
 use validators::*;
 
-const DEFAULT_TRANSACTIONS_PER_PAGE: u64 = 10;
+const MAX_DISPLAYED_COVERAGE_GAPS: usize = 20;
+const TOP_COUNTERPARTIES: usize = 20;
 
 #[derive(Debug, Clone)]
 struct AppState {
     templates: tera::Tera,
     conn: DatabaseConnection,
+    chains: Chains,
+    crawl_jobs: CrawlJobQueue,
+    pages: PagesConfig,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Params {
     page: Option<u64>,
+    internal_page: Option<u64>,
+    token_page: Option<u64>,
+    token: Option<String>,
     transactions_per_page: Option<u64>,
 }
 
+/// Transaction filters and sorting of list page, as submitted by filter form.
+/// Empty fields mean no filter, amounts are in ether.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListFilterParams {
+    from_date: Option<String>,
+    to_date: Option<String>,
+    direction: Option<String>,
+    min_value: Option<String>,
+    max_value: Option<String>,
+    counterparty: Option<String>,
+    min_fee: Option<String>,
+    status: Option<String>,
+    sort: Option<String>,
+    order: Option<String>,
+}
+
+impl ListFilterParams {
+    /// Drops empty fields, so that they don't show up in paging links
+    fn non_empty(self) -> Self {
+        let non_empty = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+
+        Self {
+            from_date: non_empty(self.from_date),
+            to_date: non_empty(self.to_date),
+            direction: non_empty(self.direction),
+            min_value: non_empty(self.min_value),
+            max_value: non_empty(self.max_value),
+            counterparty: non_empty(self.counterparty),
+            min_fee: non_empty(self.min_fee),
+            status: non_empty(self.status),
+            sort: non_empty(self.sort),
+            order: non_empty(self.order),
+        }
+    }
+
+    fn transaction_filter(
+        &self,
+        starting_block_number: u64,
+    ) -> Result<TransactionFilter, ServerError> {
+        let counterparty = self
+            .counterparty
+            .as_deref()
+            .map(parse_eth_address)
+            .transpose()?;
+
+        Ok(TransactionFilter {
+            from_block: Some(starting_block_number),
+            to_block: None,
+            from_date: parse_optional_param("from_date", &self.from_date)?,
+            to_date: parse_optional_param("to_date", &self.to_date)?,
+            direction: parse_optional_param("direction", &self.direction)?,
+            min_value: parse_optional_amount("min_value", &self.min_value)?,
+            max_value: parse_optional_amount("max_value", &self.max_value)?,
+            counterparty,
+            min_fee: parse_optional_amount("min_fee", &self.min_fee)?,
+            status: parse_optional_param("status", &self.status)?,
+        })
+    }
+
+    fn transaction_sort(&self) -> Result<TransactionSort, ServerError> {
+        Ok(TransactionSort {
+            sort: parse_optional_param("sort", &self.sort)?.unwrap_or_default(),
+            order: parse_optional_param("order", &self.order)?.unwrap_or_default(),
+        })
+    }
+}
+
+fn parse_optional_param<'de, T: Deserialize<'de>>(
+    name: &str,
+    value: &'de Option<String>,
+) -> Result<Option<T>, ServerError> {
+    value
+        .as_deref()
+        .map(|value| parse_param(name, value.trim()))
+        .transpose()
+}
+
+fn parse_optional_amount(name: &str, value: &Option<String>) -> Result<Option<U256>, ServerError> {
+    value
+        .as_deref()
+        .map(|value| parse_amount(name, value.trim(), ETHER_DECIMALS))
+        .transpose()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NftParams {
+    at_block: Option<u64>,
+    page: Option<u64>,
+    transfers_per_page: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BalanceParams {
+    at: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct TransactionFormInput {
+    /// EIP-155 id of crawled chain
+    pub chain_id: u64,
+    pub address: String,
+    pub starting_block_number: u64,
+    /// Last block to crawl, current block if empty
+    #[serde(default)]
+    pub ending_block_number: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WatchFormInput {
+    pub chain_id: u64,
     pub address: String,
     pub starting_block_number: u64,
 }
@@ -54,59 +183,158 @@ struct FlashData {
 #[get("/")]
 async fn load_transactions_data(data: web::Data<AppState>) -> Result<HttpResponse, Error> {
     let template = &data.templates;
-    let ctx = tera::Context::new();
+    let mut ctx = tera::Context::new();
+    ctx.insert("chains", &data.chains.summaries());
     let body = template
         .render("new.html.tera", &ctx)
         .map_err(|_| error::ErrorInternalServerError("Template error"))?;
     Ok(HttpResponse::Ok().content_type("text/html").body(body))
 }
 
-#[get("/list/{address}/{starting_block_number}")]
+#[get("/list/{chain}/{address}/{starting_block_number}")]
 async fn list(
     req: HttpRequest,
     data: web::Data<AppState>,
-    address_and_starting_block: web::Path<(String, u64)>,
+    chain_address_and_starting_block: web::Path<(String, String, u64)>,
+    params: web::Query<Params>,
 ) -> Result<HttpResponse, Error> {
     let template = &data.templates;
     let conn = &data.conn;
 
     // get params
-    let params = web::Query::<Params>::from_query(req.query_string()).unwrap();
-
-    let (address, starting_block_number) = address_and_starting_block.into_inner();
-
-    let page = params.page.unwrap_or(1);
-    let transactions_per_page = params
-        .transactions_per_page
-        .unwrap_or(DEFAULT_TRANSACTIONS_PER_PAGE);
+    let filter_params = web::Query::<ListFilterParams>::from_query(req.query_string())
+        .map(|filter_params| filter_params.into_inner().non_empty())
+        .unwrap_or_default();
+
+    let (chain, address, starting_block_number) = chain_address_and_starting_block.into_inner();
+    let chain = data.chains.find(&chain)?;
+    let chain_id = chain.chain_id;
+    let address = parse_eth_address(&address)?;
+    ensure_storable_number("starting_block_number", starting_block_number)?;
+    let filter = filter_params.transaction_filter(starting_block_number)?;
+    let sort = filter_params.transaction_sort()?;
+
+    // Pages are numbered from 1
+    let page = params.page.unwrap_or(1).max(1);
+    let internal_page = params.internal_page.unwrap_or(1).max(1);
+    let token_page = params.token_page.unwrap_or(1).max(1);
+    // Empty token means all tokens
+    let token = params.token.clone().filter(|token| !token.is_empty());
+    let transactions_per_page = data.pages.per_page(params.transactions_per_page);
+    ensure_valid_page("page", page, transactions_per_page)?;
+    ensure_valid_page("internal_page", internal_page, transactions_per_page)?;
+    ensure_valid_page("token_page", token_page, transactions_per_page)?;
+
+    let (transactions, total_transactions_count, num_pages) =
+        Query::find_filtered_transactions_in_page(
+            conn,
+            chain_id,
+            &address,
+            &filter,
+            &sort,
+            page,
+            transactions_per_page,
+        )
+        .await
+        .map_err(ServerError::from)?;
 
-    let (transactions, num_pages) = Query::find_transactions_in_page(
+    let (internal_transactions, num_internal_pages) = Query::find_internal_transactions_in_page(
         conn,
+        chain_id,
         address.clone(),
         starting_block_number,
-        page,
+        internal_page,
         transactions_per_page,
     )
     .await
     .map_err(ServerError::from)?;
 
-    let total_transactions_count = Query::get_transactions_count_since_block_for_selected_address(
+    let total_internal_transactions_count =
+        Query::get_internal_transactions_count_since_block_for_selected_address(
+            conn,
+            chain_id,
+            starting_block_number,
+            address.clone(),
+        )
+        .await
+        .map_err(ServerError::from)?;
+
+    let (token_transfers, num_token_pages) = Query::find_token_transfers_in_page(
         conn,
-        starting_block_number,
+        chain_id,
         address.clone(),
+        token.clone(),
+        starting_block_number,
+        token_page,
+        transactions_per_page,
     )
     .await
     .map_err(ServerError::from)?;
 
+    let total_token_transfers_count =
+        Query::get_token_transfers_count_since_block_for_selected_address(
+            conn,
+            chain_id,
+            starting_block_number,
+            address.clone(),
+            token.clone(),
+        )
+        .await
+        .map_err(ServerError::from)?;
+
+    let tokens = Query::find_tokens_for_selected_address(conn, chain_id, &address)
+        .await
+        .map_err(ServerError::from)?;
+
+    let active_crawl_job =
+        Query::find_active_crawl_job_for_selected_address(conn, chain_id, &address)
+            .await
+            .map_err(ServerError::from)?;
+
     let mut ctx = tera::Context::new();
 
+    if let Some(active_crawl_job) = active_crawl_job {
+        let flash = FlashData {
+            kind: "success".to_string(),
+            message: format!(
+                "Crawl job {} is in progress, {} rows saved so far. Refresh to see more.",
+                active_crawl_job.id, active_crawl_job.rows_saved
+            ),
+        };
+        ctx.insert("flash", &flash);
+    }
+
+    ctx.insert("chain", &chain.name);
     ctx.insert("address", &address);
     ctx.insert("starting_block_number", &starting_block_number);
+    ctx.insert("list_filter", &filter_params);
+    // Filters are carried over by paging links, export takes them in wei
+    ctx.insert(
+        "filter_query",
+        &serde_urlencoded::to_string(&filter_params).unwrap_or_default(),
+    );
+    ctx.insert(
+        "export_query",
+        &serde_urlencoded::to_string(&filter).unwrap_or_default(),
+    );
     ctx.insert("transactions", &transactions);
     ctx.insert("total_transactions_count", &total_transactions_count);
     ctx.insert("page", &page);
     ctx.insert("transactions_per_page", &transactions_per_page);
     ctx.insert("num_pages", &num_pages);
+    ctx.insert("internal_transactions", &internal_transactions);
+    ctx.insert(
+        "total_internal_transactions_count",
+        &total_internal_transactions_count,
+    );
+    ctx.insert("internal_page", &internal_page);
+    ctx.insert("num_internal_pages", &num_internal_pages);
+    ctx.insert("token_transfers", &token_transfers);
+    ctx.insert("total_token_transfers_count", &total_token_transfers_count);
+    ctx.insert("token_page", &token_page);
+    ctx.insert("num_token_pages", &num_token_pages);
+    ctx.insert("tokens", &tokens);
+    ctx.insert("token", &token.unwrap_or_default());
 
     let body = template
         .render("index.html.tera", &ctx)
@@ -114,6 +342,242 @@ async fn list(
     Ok(HttpResponse::Ok().content_type("text/html").body(body))
 }
 
+#[get("/nfts/{chain}/{address}")]
+async fn nfts(
+    data: web::Data<AppState>,
+    chain_and_address: web::Path<(String, String)>,
+    params: web::Query<NftParams>,
+) -> Result<HttpResponse, Error> {
+    let template = &data.templates;
+    let conn = &data.conn;
+
+    let (chain, address) = chain_and_address.into_inner();
+    let chain = data.chains.find(&chain)?;
+    let address = parse_eth_address(&address)?;
+
+    let page = params.page.unwrap_or(1).max(1);
+    let transfers_per_page = data.pages.per_page(params.transfers_per_page);
+    ensure_valid_page("page", page, transfers_per_page)?;
+    if let Some(at_block) = params.at_block {
+        ensure_storable_number("at_block", at_block)?;
+    }
+
+    let nft_holdings =
+        Query::get_nft_holdings_at_block(conn, chain.chain_id, &address, params.at_block)
+            .await
+            .map_err(ServerError::from)?;
+
+    let (nft_transfers, num_pages) = Query::find_nft_transfers_in_page(
+        conn,
+        chain.chain_id,
+        address.clone(),
+        page,
+        transfers_per_page,
+    )
+    .await
+    .map_err(ServerError::from)?;
+
+    let mut ctx = tera::Context::new();
+
+    ctx.insert("chain", &chain.name);
+    ctx.insert("address", &address);
+    ctx.insert("at_block", &params.at_block);
+    ctx.insert("nft_holdings", &nft_holdings.holdings);
+
+    if !nft_holdings.consistent {
+        let flash = FlashData {
+            kind: "error".to_string(),
+            message: "Holdings are incomplete. Address sent NFTs, which it didn't receive \
+                      in crawled transfers, crawl earlier blocks to include them."
+                .to_string(),
+        };
+        ctx.insert("flash", &flash);
+    }
+    ctx.insert("nft_transfers", &nft_transfers);
+    ctx.insert("page", &page);
+    ctx.insert("transfers_per_page", &transfers_per_page);
+    ctx.insert("num_pages", &num_pages);
+
+    let body = template
+        .render("nfts.html.tera", &ctx)
+        .map_err(|_| error::ErrorInternalServerError("Template error"))?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(body))
+}
+
+#[get("/balance/{chain}/{address}")]
+async fn balance(
+    data: web::Data<AppState>,
+    chain_and_address: web::Path<(String, String)>,
+    params: web::Query<BalanceParams>,
+) -> Result<HttpResponse, Error> {
+    let template = &data.templates;
+    let conn = &data.conn;
+
+    let (chain, address) = chain_and_address.into_inner();
+    let chain = data.chains.find(&chain)?;
+    let source = &chain.source;
+    let address = parse_eth_address(&address)?;
+
+    // Anchor replay on current balance
+    let anchor_block = source.get_current_block_number().await?;
+    let anchor_balance = source.get_balance(&address).await?;
+
+    let at = match params.at.as_deref().filter(|at| !at.is_empty()) {
+        Some(at) => parse_block_or_date(at)?,
+        None => BlockOrDate::Block(anchor_block),
+    };
+
+    let at_block = match at {
+        BlockOrDate::Block(block_number) => block_number,
+        BlockOrDate::Date(date) => {
+            let next_day_start = date
+                .succ_opt()
+                .and_then(|next_day| next_day.and_hms_opt(0, 0, 0))
+                .ok_or_else(|| ServerError::InvalidBlockOrDate {
+                    value: date.to_string(),
+                })?;
+            source
+                .get_block_number_before(next_day_start.and_utc().timestamp() - 1)
+                .await?
+        }
+    };
+
+    // Blocks after requested point, which were not crawled, make balance incomplete
+    let coverage_gaps = if at_block < anchor_block {
+        let mut coverage_gaps = vec![];
+
+        for coverage_source in [
+            CoverageSource::Transactions,
+            CoverageSource::InternalTransactions,
+        ] {
+            coverage_gaps.extend(
+                Query::find_uncovered_block_ranges(
+                    conn,
+                    chain.chain_id,
+                    &address,
+                    coverage_source,
+                    (at_block + 1, anchor_block),
+                )
+                .await
+                .map_err(ServerError::from)?,
+            );
+        }
+
+        merge_block_ranges(coverage_gaps)
+    } else {
+        vec![]
+    };
+
+    let balance_at = Query::balance_at(
+        conn,
+        chain.chain_id,
+        &address,
+        at,
+        anchor_block,
+        anchor_balance,
+    )
+    .await
+    .map_err(ServerError::from)?;
+
+    let mut ctx = tera::Context::new();
+
+    ctx.insert("chain", &chain.name);
+    ctx.insert("address", &address);
+    ctx.insert("at", &params.at.clone().unwrap_or_default());
+    ctx.insert("at_block", &at_block);
+    ctx.insert("balance_at", &balance_at);
+
+    if !coverage_gaps.is_empty() || !balance_at.consistent {
+        let mut message = "Balance is incomplete.".to_string();
+
+        if !balance_at.consistent {
+            message += " Crawled transactions don't add up to the current balance.";
+        }
+
+        if !coverage_gaps.is_empty() {
+            let displayed_coverage_gaps: Vec<String> = coverage_gaps
+                .iter()
+                .take(MAX_DISPLAYED_COVERAGE_GAPS)
+                .map(|(start, end)| format!("{start}-{end}"))
+                .collect();
+            message += &format!(
+                " {} block ranges between block {} and block {} were not crawled: {}{}",
+                coverage_gaps.len(),
+                at_block + 1,
+                anchor_block,
+                displayed_coverage_gaps.join(", "),
+                if coverage_gaps.len() > MAX_DISPLAYED_COVERAGE_GAPS {
+                    ", ..."
+                } else {
+                    ""
+                }
+            );
+        }
+
+        let flash = FlashData {
+            kind: "error".to_string(),
+            message,
+        };
+        ctx.insert("flash", &flash);
+    }
+
+    let body = template
+        .render("balance.html.tera", &ctx)
+        .map_err(|_| error::ErrorInternalServerError("Template error"))?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(body))
+}
+
+#[get("/address/{chain}/{address}")]
+async fn address_summary(
+    data: web::Data<AppState>,
+    chain_and_address: web::Path<(String, String)>,
+) -> Result<HttpResponse, Error> {
+    let template = &data.templates;
+    let conn = &data.conn;
+
+    let (chain, address) = chain_and_address.into_inner();
+    let chain = data.chains.find(&chain)?;
+    let address = parse_eth_address(&address)?;
+
+    let activity = Query::get_address_activity(conn, chain.chain_id, &address, TOP_COUNTERPARTIES)
+        .await
+        .map_err(ServerError::from)?;
+
+    let mut ctx = tera::Context::new();
+
+    ctx.insert("chain", &chain.name);
+    ctx.insert("address", &address);
+    ctx.insert("summary", &activity.summary);
+    ctx.insert("top_counterparties", &activity.top_counterparties);
+    ctx.insert("monthly_activity", &activity.monthly_activity);
+
+    let body = template
+        .render("address.html.tera", &ctx)
+        .map_err(|_| error::ErrorInternalServerError("Template error"))?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(body))
+}
+
+#[get("/jobs/{id}")]
+async fn crawl_job(data: web::Data<AppState>, id: web::Path<i32>) -> Result<HttpResponse, Error> {
+    let template = &data.templates;
+    let conn = &data.conn;
+
+    let id = id.into_inner();
+
+    let job = Query::find_crawl_job_by_id(conn, id)
+        .await
+        .map_err(ServerError::from)?
+        .ok_or(ServerError::CrawlJobNotFound { id })?;
+
+    let mut ctx = tera::Context::new();
+    ctx.insert("job", &job);
+
+    let body = template
+        .render("job.html.tera", &ctx)
+        .map_err(|_| error::ErrorInternalServerError("Template error"))?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(body))
+}
+
 #[post("/")]
 async fn create(
     data: web::Data<AppState>,
@@ -123,47 +587,124 @@ async fn create(
 
     let form = transaction_form.into_inner();
 
-    ensure_valid_eth_address(&form.address)?;
+    let chain = data.chains.get(form.chain_id)?;
+    let address = parse_eth_address(&form.address)?;
+    ensure_valid_block_range(form.starting_block_number, form.ending_block_number)?;
 
-    let current_block_number = get_current_block_number().await?;
+    // Crawl runs in background, progress is shown on job page
+    let job = Mutation::create_crawl_job(
+        conn,
+        chain.chain_id,
+        address,
+        form.starting_block_number,
+        form.ending_block_number,
+    )
+    .await
+    .map_err(ServerError::from)?;
+    data.crawl_jobs.enqueue(job.id)?;
 
-    ensure_valid_starting_block_number(form.starting_block_number, current_block_number)?;
+    Ok(HttpResponse::Found()
+        .append_header(("location", format!("/jobs/{}", job.id)))
+        .finish())
+}
 
-    let fetched_block_numbers_since_block =
-        Query::get_block_numbers_since_block_for_selected_address(
-            conn,
-            &form.address,
-            form.starting_block_number,
-        )
+#[get("/watches")]
+async fn list_watches(data: web::Data<AppState>) -> Result<HttpResponse, Error> {
+    let template = &data.templates;
+    let conn = &data.conn;
+
+    let watched_addresses = Query::find_watched_addresses(conn)
         .await
         .map_err(ServerError::from)?;
 
-    let block_ranges_for_unfetched_transactions = get_block_ranges_for_unfetched_transactions(
-        fetched_block_numbers_since_block,
-        form.starting_block_number,
-        current_block_number,
-    );
+    let mut ctx = tera::Context::new();
+    ctx.insert("chains", &data.chains.summaries());
+    ctx.insert("watched_addresses", &watched_addresses);
 
-    let mut unfetched_transactions = vec![];
+    let body = template
+        .render("watches.html.tera", &ctx)
+        .map_err(|_| error::ErrorInternalServerError("Template error"))?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(body))
+}
+
+#[post("/watches")]
+async fn create_watch(
+    data: web::Data<AppState>,
+    watch_form: web::Form<WatchFormInput>,
+) -> Result<HttpResponse, Error> {
+    let conn = &data.conn;
 
-    for block_range_for_unfetched_transactions in block_ranges_for_unfetched_transactions {
-        let transactions =
-            fetch_transactions(block_range_for_unfetched_transactions, &form).await?;
-        unfetched_transactions.extend(transactions);
+    let form = watch_form.into_inner();
+
+    let chain = data.chains.get(form.chain_id)?;
+    let address = parse_eth_address(&form.address)?;
+    ensure_storable_number("starting_block_number", form.starting_block_number)?;
+
+    if Query::find_watched_address_by_address(conn, chain.chain_id, &address)
+        .await
+        .map_err(ServerError::from)?
+        .is_some()
+    {
+        return Err(ServerError::AddressAlreadyWatched { address }.into());
     }
 
-    if !unfetched_transactions.is_empty() {
-        Mutation::save_transactions(conn, unfetched_transactions)
-            .await
-            .map_err(ServerError::from)?;
+    // First crawl is run by scheduler on its next tick
+    Mutation::create_watched_address(conn, chain.chain_id, address, form.starting_block_number)
+        .await
+        .map_err(ServerError::from)?;
+
+    Ok(redirect_to_watches())
+}
+
+#[post("/watches/{id}/pause")]
+async fn pause_watch(data: web::Data<AppState>, id: web::Path<i32>) -> Result<HttpResponse, Error> {
+    set_watch_status(&data.conn, id.into_inner(), WatchStatus::Paused).await
+}
+
+#[post("/watches/{id}/resume")]
+async fn resume_watch(
+    data: web::Data<AppState>,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, Error> {
+    set_watch_status(&data.conn, id.into_inner(), WatchStatus::Active).await
+}
+
+#[post("/watches/{id}/delete")]
+async fn delete_watch(
+    data: web::Data<AppState>,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, Error> {
+    let id = id.into_inner();
+
+    if !Mutation::delete_watched_address(&data.conn, id)
+        .await
+        .map_err(ServerError::from)?
+    {
+        return Err(ServerError::WatchedAddressNotFound { id }.into());
     }
 
-    Ok(HttpResponse::Found()
-        .append_header((
-            "location",
-            "/list/".to_string() + &form.address + "/" + &form.starting_block_number.to_string(),
-        ))
-        .finish())
+    Ok(redirect_to_watches())
+}
+
+async fn set_watch_status(
+    conn: &DatabaseConnection,
+    id: i32,
+    status: WatchStatus,
+) -> Result<HttpResponse, Error> {
+    if !Mutation::update_watched_address_status(conn, id, status)
+        .await
+        .map_err(ServerError::from)?
+    {
+        return Err(ServerError::WatchedAddressNotFound { id }.into());
+    }
+
+    Ok(redirect_to_watches())
+}
+
+fn redirect_to_watches() -> HttpResponse {
+    HttpResponse::Found()
+        .append_header(("location", "/watches"))
+        .finish()
 }
 
 async fn not_found(data: web::Data<AppState>, request: HttpRequest) -> Result<HttpResponse, Error> {
@@ -178,26 +719,69 @@ async fn not_found(data: web::Data<AppState>, request: HttpRequest) -> Result<Ht
     Ok(HttpResponse::Ok().content_type("text/html").body(body))
 }
 
-#[actix_web::main]
-async fn start() -> std::io::Result<()> {
-    env::set_var("RUST_LOG", "debug");
-    tracing_subscriber::fmt::init();
+/// Validated config along with transaction source clients of every chain built from it
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub config: Config,
+    pub chains: Chains,
+}
 
-    // get env vars
-    dotenvy::dotenv().ok();
-    let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set in .env file");
-    let host = env::var("HOST").expect("HOST is not set in .env file");
-    let port = env::var("PORT").expect("PORT is not set in .env file");
-    let server_url = format!("{host}:{port}");
+impl Settings {
+    pub fn new(config: Config) -> Result<Self, ServerError> {
+        let chains = Chains::new(&config)?;
+
+        Ok(Self { config, chains })
+    }
+}
+
+/// Applies pending migrations, starts background crawl worker and scheduler and serves web app
+async fn serve(settings: Settings) -> std::io::Result<()> {
+    let Settings { config, chains } = settings;
+    let server_url = format!("{}:{}", config.server.host, config.server.port);
+    for chain in chains.iter() {
+        println!(
+            "Using {} transaction source for chain {} ({})",
+            chain.source.name(),
+            chain.name,
+            chain.chain_id
+        );
+    }
 
     // establish connection to database and apply migrations
     // -> create post table if not exists
-    let conn = Database::connect(&db_url).await.unwrap();
-    Migrator::up(&conn, None).await.unwrap();
+    let conn = connect(&config.database).await.map_err(io::Error::other)?;
+    Migrator::up(&conn, None).await.map_err(io::Error::other)?;
+
+    // start background crawl worker
+    let crawl_jobs = spawn_crawl_worker(
+        conn.clone(),
+        chains.clone(),
+        config.crawler.confirmation_depth,
+        config.crawler.reorg_recheck_blocks,
+    )
+    .await
+    .map_err(io::Error::other)?;
+
+    // start scheduler of reorg re-checks and watched address crawls
+    spawn_scheduler(
+        conn.clone(),
+        chains.clone(),
+        crawl_jobs.clone(),
+        Duration::from_secs(config.crawler.watch_interval_secs),
+        config.crawler.confirmation_depth,
+        config.crawler.reorg_recheck_blocks,
+    );
 
     // load tera templates and build app state
-    let templates = Tera::new(concat!(env!("CARGO_MANIFEST_DIR"), "/templates/**/*")).unwrap();
-    let state = AppState { templates, conn };
+    let mut templates = Tera::new(concat!(env!("CARGO_MANIFEST_DIR"), "/templates/**/*")).unwrap();
+    templates.register_filter("format_units", format_units);
+    let state = AppState {
+        templates,
+        conn,
+        chains,
+        crawl_jobs,
+        pages: config.pages,
+    };
 
     // create server and try to serve over socket if possible
     let mut listenfd = ListenFd::from_env();
@@ -221,16 +805,34 @@ async fn start() -> std::io::Result<()> {
     Ok(())
 }
 
+/// Connects to database with configured pool size
+async fn connect(database: &DatabaseConfig) -> Result<DatabaseConnection, ServerError> {
+    let url = database
+        .url
+        .clone()
+        .expect("database url is validated on startup");
+    let mut options = ConnectOptions::new(url);
+    options.max_connections(database.max_connections);
+
+    Database::connect(options).await.map_err(ServerError::from)
+}
+
 fn init(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/api/v1").configure(api_v1::init));
     cfg.service(load_transactions_data);
     cfg.service(list);
+    cfg.service(nfts);
+    cfg.service(balance);
+    cfg.service(address_summary);
+    cfg.service(crawl_job);
+    cfg.service(list_watches);
+    cfg.service(create_watch);
+    cfg.service(pause_watch);
+    cfg.service(resume_watch);
+    cfg.service(delete_watch);
     cfg.service(create);
 }
 
-pub fn main() {
-    let result = start();
-
-    if let Some(err) = result.err() {
-        println!("Error: {err}");
-    }
+pub fn main() -> ExitCode {
+    cli::run()
 }
@@ -0,0 +1,195 @@
+use actix_web::rt::time::sleep;
+use rand::Rng;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+// Etherscan free api keys allow 5 requests per second
+pub const DEFAULT_REQUESTS_PER_SECOND: f64 = 5.0;
+
+// Default retry settings
+pub const DEFAULT_MAX_RETRIES: u32 = 5;
+pub const DEFAULT_BASE_RETRY_DELAY: Duration = Duration::from_millis(500);
+pub const DEFAULT_MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Token bucket, shared by all clones. Holds up to one second worth of requests.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    requests_per_second: f64,
+    tokens: f64,
+    refilled_at: Instant,
+    paused_until: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64) -> Self {
+        Self {
+            bucket: Arc::new(Mutex::new(Bucket {
+                requests_per_second,
+                tokens: requests_per_second.max(1.0),
+                refilled_at: Instant::now(),
+                paused_until: None,
+            })),
+        }
+    }
+
+    /// Waits until request can be sent
+    pub async fn acquire(&self) {
+        loop {
+            let wait = self.bucket.lock().unwrap().take(Instant::now());
+
+            match wait {
+                Some(wait) => sleep(wait).await,
+                None => return,
+            }
+        }
+    }
+
+    /// Holds back all requests for given duration, e.g. after explorer reported exceeded rate limit
+    pub fn pause(&self, duration: Duration) {
+        let mut bucket = self.bucket.lock().unwrap();
+        let paused_until = Instant::now() + duration;
+
+        bucket.paused_until = bucket.paused_until.max(Some(paused_until));
+        bucket.tokens = 0.0;
+    }
+}
+
+impl Bucket {
+    /// Takes token if there is one, otherwise returns time to wait for it
+    fn take(&mut self, now: Instant) -> Option<Duration> {
+        if let Some(paused_until) = self.paused_until {
+            if paused_until > now {
+                return Some(paused_until - now);
+            }
+            self.paused_until = None;
+            self.refilled_at = paused_until;
+        }
+
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.requests_per_second)
+            .min(self.requests_per_second.max(1.0));
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.requests_per_second,
+            ))
+        }
+    }
+}
+
+/// Exponential backoff with full jitter
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: DEFAULT_BASE_RETRY_DELAY,
+            max_delay: DEFAULT_MAX_RETRY_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Random delay before given retry, attempts are counted from zero
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+
+        ceiling.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket(requests_per_second: f64, now: Instant) -> Bucket {
+        Bucket {
+            requests_per_second,
+            tokens: requests_per_second.max(1.0),
+            refilled_at: now,
+            paused_until: None,
+        }
+    }
+
+    #[test]
+    fn takes_one_second_worth_of_requests_then_waits() {
+        let now = Instant::now();
+        let mut bucket = bucket(5.0, now);
+
+        for _ in 0..5 {
+            assert_eq!(bucket.take(now), None);
+        }
+        assert_eq!(bucket.take(now), Some(Duration::from_millis(200)));
+
+        // Token is refilled after 1 / requests_per_second
+        assert_eq!(bucket.take(now + Duration::from_millis(200)), None);
+    }
+
+    #[test]
+    fn does_not_refill_beyond_one_second_worth() {
+        let now = Instant::now();
+        let mut bucket = bucket(2.0, now);
+        let later = now + Duration::from_secs(60);
+
+        assert_eq!(bucket.take(later), None);
+        assert_eq!(bucket.take(later), None);
+        assert!(bucket.take(later).is_some());
+    }
+
+    #[test]
+    fn slow_rate_holds_single_request() {
+        let now = Instant::now();
+        let mut bucket = bucket(0.5, now);
+
+        assert_eq!(bucket.take(now), None);
+        assert_eq!(bucket.take(now), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn pause_holds_back_requests() {
+        let limiter = RateLimiter::new(5.0);
+        limiter.pause(Duration::from_secs(10));
+
+        let mut bucket = limiter.bucket.lock().unwrap();
+        let now = Instant::now();
+        let wait = bucket.take(now).unwrap();
+        assert!(wait > Duration::from_secs(9) && wait <= Duration::from_secs(10));
+
+        // After pause, bucket refills from the end of pause
+        let paused_until = bucket.paused_until.unwrap();
+        assert!(bucket.take(paused_until).is_some());
+        assert_eq!(bucket.take(paused_until + Duration::from_millis(200)), None);
+    }
+
+    #[test]
+    fn retry_delay_stays_within_ceiling() {
+        let policy = RetryPolicy::default();
+
+        for attempt in 0..40 {
+            let ceiling = DEFAULT_BASE_RETRY_DELAY
+                .saturating_mul(2u32.saturating_pow(attempt))
+                .min(DEFAULT_MAX_RETRY_DELAY);
+            assert!(policy.delay(attempt) <= ceiling);
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use crate::errors::*;
+use entity::u256::{RawU256, U256};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+
+// Etherscan statuses
+pub const STATUS_OK: &str = "1";
+
+/// Envelope of `module=account` responses.
+/// On success `result` holds requested data, otherwise it holds an error message.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EtherscanResponse {
+    pub status: String,
+    pub message: String,
+    pub result: Value,
+}
+
+impl EtherscanResponse {
+    /// Deserializes `result` if explorer replied with status "1", classifies error otherwise
+    pub fn into_result<T: DeserializeOwned>(self) -> Result<T, ServerError> {
+        if self.status != STATUS_OK {
+            return Err(self.into_error());
+        }
+
+        serde_json::from_value(self.result).map_err(|e| ServerError::MalformedResponse {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Maps status "0" / "NOTOK" reply to corresponding `ServerError`
+    pub fn into_error(self) -> ServerError {
+        let details = match self.result {
+            Value::String(details) => details,
+            _ => String::new(),
+        };
+
+        classify_explorer_error(&self.message, &details)
+    }
+}
+
+/// Envelope of `module=proxy` responses, which follow JSON-RPC format
+#[derive(Debug, Clone, Deserialize)]
+pub struct JsonRpcResponse<T> {
+    pub jsonrpc: String,
+    pub result: Option<T>,
+    pub error: Option<JsonRpcError>,
+}
+
+impl<T> JsonRpcResponse<T> {
+    pub fn into_result(self) -> Result<T, ServerError> {
+        match self {
+            Self {
+                error: Some(error), ..
+            } => Err(classify_explorer_error(&error.message, &error.message)),
+            Self {
+                result: Some(result),
+                ..
+            } => Ok(result),
+            _ => Err(ServerError::MalformedResponse {
+                reason: "JSON-RPC response has neither result nor error".to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+/// Proxy module replies in JSON-RPC format, but rate limit and api key
+/// errors still come in regular Etherscan envelope
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ProxyResponse<T> {
+    JsonRpc(JsonRpcResponse<T>),
+    Etherscan(EtherscanResponse),
+}
+
+impl<T> ProxyResponse<T> {
+    pub fn into_result(self) -> Result<T, ServerError> {
+        match self {
+            Self::JsonRpc(response) => response.into_result(),
+            Self::Etherscan(response) => Err(response.into_error()),
+        }
+    }
+}
+
+/// Result of `module=proxy&action=eth_blockNumber`, hex encoded block number
+pub type BlockNumberResponse = ProxyResponse<String>;
+
+/// Result of `module=proxy&action=eth_getBlockByNumber` without full transactions
+pub type BlockResponse = ProxyResponse<RpcBlock<String>>;
+
+/// Entry of `module=account` list result, which explorer sorts by block
+pub trait AccountListEntry: DeserializeOwned {
+    fn block_number(&self) -> &str;
+}
+
+impl AccountListEntry for EtherscanTransaction {
+    fn block_number(&self) -> &str {
+        &self.block_number
+    }
+}
+
+impl AccountListEntry for EtherscanInternalTransaction {
+    fn block_number(&self) -> &str {
+        &self.block_number
+    }
+}
+
+impl AccountListEntry for EtherscanTokenTransfer {
+    fn block_number(&self) -> &str {
+        &self.block_number
+    }
+}
+
+impl AccountListEntry for EtherscanNftTransfer {
+    fn block_number(&self) -> &str {
+        &self.block_number
+    }
+}
+
+/// Single entry of `module=account&action=txlist` result
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EtherscanTransaction {
+    pub block_number: String,
+    #[serde(default)]
+    pub block_hash: String,
+    pub time_stamp: String,
+    pub hash: String,
+    pub from: String,
+    /// Empty for contract creations
+    #[serde(default)]
+    pub to: Option<String>,
+    pub value: String,
+    pub nonce: String,
+    pub transaction_index: String,
+    pub gas: String,
+    pub gas_used: String,
+    pub gas_price: String,
+    pub cumulative_gas_used: String,
+    pub input: String,
+    #[serde(default)]
+    pub method_id: String,
+    #[serde(default)]
+    pub function_name: String,
+    #[serde(default)]
+    pub contract_address: String,
+    #[serde(default)]
+    pub is_error: String,
+    /// Empty for transactions before Byzantium fork
+    #[serde(rename = "txreceipt_status", default)]
+    pub receipt_status: String,
+}
+
+/// Single entry of `module=account&action=txlistinternal` result, `hash` is parent transaction hash
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EtherscanInternalTransaction {
+    pub block_number: String,
+    pub time_stamp: String,
+    pub hash: String,
+    pub from: String,
+    pub to: String,
+    pub value: String,
+    pub contract_address: String,
+    pub r#type: String,
+    pub trace_id: String,
+    pub is_error: String,
+}
+
+/// Single entry of `module=account&action=tokentx` result.
+/// Log index isn't returned by every explorer, so it's optional.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EtherscanTokenTransfer {
+    pub block_number: String,
+    #[serde(default)]
+    pub block_hash: String,
+    pub time_stamp: String,
+    pub hash: String,
+    pub from: String,
+    pub to: String,
+    pub value: String,
+    pub contract_address: String,
+    pub token_name: String,
+    pub token_symbol: String,
+    pub token_decimal: String,
+    #[serde(default)]
+    pub log_index: Option<String>,
+}
+
+/// Single entry of `module=account&action=tokennfttx` and `module=account&action=token1155tx` results.
+/// Token value is returned for ERC-1155 transfers only.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EtherscanNftTransfer {
+    pub block_number: String,
+    #[serde(default)]
+    pub block_hash: String,
+    pub time_stamp: String,
+    pub hash: String,
+    pub from: String,
+    pub to: String,
+    pub contract_address: String,
+    #[serde(rename = "tokenID")]
+    pub token_id: String,
+    #[serde(default)]
+    pub token_value: Option<String>,
+    #[serde(default)]
+    pub log_index: Option<String>,
+}
+
+/// Block, returned by `eth_getBlockByNumber`.
+/// Transactions are either hashes or full transaction objects, depending on request.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBlock<T> {
+    pub number: String,
+    pub hash: String,
+    pub timestamp: String,
+    /// Missing before London fork
+    pub base_fee_per_gas: Option<String>,
+    pub transactions: Vec<T>,
+}
+
+/// Transaction object, returned by `eth_getTransactionByHash` and `eth_getBlockByNumber` with full transactions
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransaction {
+    pub hash: String,
+    pub nonce: String,
+    pub transaction_index: String,
+    pub from: String,
+    pub to: Option<String>,
+    pub value: String,
+    pub gas: String,
+    pub gas_price: Option<String>,
+    pub input: String,
+    /// Missing for pre-Berlin transactions
+    pub r#type: Option<String>,
+    pub max_fee_per_gas: Option<String>,
+    pub max_priority_fee_per_gas: Option<String>,
+}
+
+/// Receipt, returned by `eth_getTransactionReceipt`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcReceipt {
+    /// `0x1` for success, `0x0` for failure, missing before Byzantium fork
+    pub status: Option<String>,
+    pub gas_used: String,
+    pub cumulative_gas_used: String,
+    pub effective_gas_price: Option<String>,
+    pub contract_address: Option<String>,
+    /// Present for blob transactions only
+    pub blob_gas_used: Option<String>,
+    pub blob_gas_price: Option<String>,
+}
+
+/// Parses numeric field, returned by Etherscan as a decimal string
+pub fn parse_field<T: std::str::FromStr>(value: &str, field: &str) -> Result<T, ServerError> {
+    value.parse().map_err(|_| ServerError::MalformedResponse {
+        reason: format!("invalid {field}: {value:?}"),
+    })
+}
+
+/// Parses hex encoded quantity, returned by JSON-RPC methods
+pub fn parse_hex_quantity(value: &str, field: &str) -> Result<u64, ServerError> {
+    u64::from_str_radix(value.trim_start_matches("0x"), 16).map_err(|_| {
+        ServerError::MalformedResponse {
+            reason: format!("invalid {field}: {value:?}"),
+        }
+    })
+}
+
+/// Parses hex encoded 256-bit quantity, returned by JSON-RPC methods
+pub fn parse_hex_u256(value: &str, field: &str) -> Result<U256, ServerError> {
+    RawU256::from_str_radix(value.trim_start_matches("0x"), 16)
+        .map(U256)
+        .map_err(|_| ServerError::MalformedResponse {
+            reason: format!("invalid {field}: {value:?}"),
+        })
+}
+
+/// Rate limit message of failed Etherscan response, if it's the reason of failure.
+/// Rate limit replies come as regular envelopes with `NOTOK` status.
+pub fn rate_limit_message(resp: &Value) -> Option<&str> {
+    let status = resp.get("status")?.as_str()?;
+    let result = resp.get("result")?.as_str()?;
+
+    (status == "0" && result.to_lowercase().contains("rate limit")).then_some(result)
+}
+
+fn classify_explorer_error(message: &str, details: &str) -> ServerError {
+    let message_lowercase = message.to_lowercase();
+    let details_lowercase = details.to_lowercase();
+
+    if message_lowercase.starts_with("no ") && message_lowercase.ends_with(" found") {
+        ServerError::NoResults
+    } else if details_lowercase.contains("rate limit") {
+        ServerError::RateLimited {
+            message: details.to_string(),
+        }
+    } else if details_lowercase.contains("api key") {
+        ServerError::InvalidApiKey {
+            message: details.to_string(),
+        }
+    } else if details_lowercase.contains("result window is too large") {
+        ServerError::ResultWindowExceeded
+    } else {
+        ServerError::ExplorerError {
+            message: if details.is_empty() {
+                message.to_string()
+            } else {
+                details.to_string()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_explorer_errors() {
+        assert!(matches!(
+            classify_explorer_error("No transactions found", ""),
+            ServerError::NoResults
+        ));
+        assert!(matches!(
+            classify_explorer_error("NOTOK", "Max rate limit reached"),
+            ServerError::RateLimited { .. }
+        ));
+        assert!(matches!(
+            classify_explorer_error("NOTOK", "Invalid API Key"),
+            ServerError::InvalidApiKey { .. }
+        ));
+        assert!(matches!(
+            classify_explorer_error("NOTOK", "Result window is too large, PageNo x Offset size must be less than or equal to 10000"),
+            ServerError::ResultWindowExceeded
+        ));
+    }
+
+    #[test]
+    fn unknown_explorer_errors_keep_their_message() {
+        match classify_explorer_error("NOTOK", "Query Timeout occured") {
+            ServerError::ExplorerError { message } => assert_eq!(message, "Query Timeout occured"),
+            err => panic!("unexpected error: {err:?}"),
+        }
+        match classify_explorer_error("NOTOK", "") {
+            ServerError::ExplorerError { message } => assert_eq!(message, "NOTOK"),
+            err => panic!("unexpected error: {err:?}"),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{errors::ServerError, TransactionFormInput};
+use async_trait::async_trait;
+use entity::{transactions, u256::U256};
+use serde::Deserialize;
+use std::{fmt::Debug, str::FromStr};
+
+/// Backend, which provides chain head, balances and transactions of an address
+#[async_trait]
+pub trait TransactionSource: Debug + Send + Sync {
+    /// Short backend name, shown in logs
+    fn name(&self) -> &'static str;
+
+    /// Get current eth block number
+    async fn get_current_block_number(&self) -> Result<u64, ServerError>;
+
+    /// Get hash of block with given number
+    async fn get_block_hash(&self, block_number: u64) -> Result<String, ServerError>;
+
+    /// Get current balance of given address in wei
+    async fn get_balance(&self, address: &str) -> Result<U256, ServerError>;
+
+    /// Get number of the last block mined before given unix timestamp
+    async fn get_block_number_before(&self, timestamp: i64) -> Result<u64, ServerError>;
+
+    /// Fetch all transactions of given address within block range
+    async fn fetch_transactions(
+        &self,
+        block_range: (u64, u64),
+        transaction_form_input: &TransactionFormInput,
+        progress: &dyn FetchProgress,
+    ) -> Result<Vec<transactions::Model>, ServerError>;
+}
+
+/// Gets notified about every page of results fetched from a source
+pub trait FetchProgress: Send + Sync {
+    fn page_fetched(&self);
+}
+
+/// Ignores progress, when nobody is watching
+impl FetchProgress for () {
+    fn page_fetched(&self) {}
+}
+
+/// Available transaction sources
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionSourceKind {
+    Etherscan,
+    JsonRpc,
+}
+
+impl FromStr for TransactionSourceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "etherscan" => Ok(Self::Etherscan),
+            "json_rpc" => Ok(Self::JsonRpc),
+            _ => Err(format!(
+                "Unknown transaction source {s:?}, expected \"etherscan\" or \"json_rpc\""
+            )),
+        }
+    }
+}
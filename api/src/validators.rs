@@ -1,5 +1,12 @@
 use crate::errors::*;
+use actix_example_service::{BlockOrDate, TransactionFilter};
+use chrono::NaiveDate;
+use entity::u256::U256;
 use eth_address::address::is_address;
+use serde::{
+    de::value::{Error as ValueError, StrDeserializer},
+    Deserialize,
+};
 
 pub fn ensure_valid_eth_address(address: &str) -> Result<(), ServerError> {
     if !is_address(address.to_string()) {
@@ -11,6 +18,83 @@ pub fn ensure_valid_eth_address(address: &str) -> Result<(), ServerError> {
     }
 }
 
+/// Validates address and lowercases it, like explorers and nodes return addresses.
+/// Unlike MySQL, other databases compare strings case-sensitively.
+pub fn parse_eth_address(address: &str) -> Result<String, ServerError> {
+    ensure_valid_eth_address(address)?;
+
+    Ok(address.to_lowercase())
+}
+
+/// Largest number, which fits into signed 64-bit columns of PostgreSQL and SQLite
+pub const MAX_STORED_NUMBER: u64 = i64::MAX as u64;
+
+/// Rejects numbers, which PostgreSQL and SQLite can't store or compare against
+pub fn ensure_storable_number(name: &str, value: u64) -> Result<(), ServerError> {
+    if value > MAX_STORED_NUMBER {
+        Err(ServerError::InvalidParameter {
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Rejects page, which starts beyond the largest storable row offset
+pub fn ensure_valid_page(name: &str, page: u64, per_page: u64) -> Result<(), ServerError> {
+    if page > MAX_STORED_NUMBER / per_page.max(1) {
+        Err(ServerError::InvalidParameter {
+            name: name.to_string(),
+            value: page.to_string(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Rejects block bounds of filter, which can't be stored
+pub fn ensure_storable_filter(filter: &TransactionFilter) -> Result<(), ServerError> {
+    if let Some(from_block) = filter.from_block {
+        ensure_storable_number("from_block", from_block)?;
+    }
+    if let Some(to_block) = filter.to_block {
+        ensure_storable_number("to_block", to_block)?;
+    }
+
+    Ok(())
+}
+
+/// Validates transaction hash and lowercases it, like addresses
+pub fn parse_tx_hash(hash: &str) -> Result<String, ServerError> {
+    let is_hash = hash.len() == 66
+        && hash.starts_with("0x")
+        && hash[2..].bytes().all(|b| b.is_ascii_hexdigit());
+
+    if !is_hash {
+        return Err(ServerError::InvalidParameter {
+            name: "transaction hash".to_string(),
+            value: hash.to_string(),
+        });
+    }
+
+    Ok(hash.to_lowercase())
+}
+
+/// Validates filter given by API or command line and lowercases its counterparty
+pub fn normalize_filter(filter: TransactionFilter) -> Result<TransactionFilter, ServerError> {
+    ensure_storable_filter(&filter)?;
+
+    Ok(TransactionFilter {
+        counterparty: filter
+            .counterparty
+            .as_deref()
+            .map(parse_eth_address)
+            .transpose()?,
+        ..filter
+    })
+}
+
 pub fn ensure_valid_starting_block_number(
     starting_block_number: u64,
     current_block_number: u64,
@@ -24,3 +108,115 @@ pub fn ensure_valid_starting_block_number(
         Ok(())
     }
 }
+
+pub fn ensure_valid_block_range(
+    starting_block_number: u64,
+    ending_block_number: Option<u64>,
+) -> Result<(), ServerError> {
+    ensure_storable_number("starting_block_number", starting_block_number)?;
+    if let Some(ending_block_number) = ending_block_number {
+        ensure_storable_number("ending_block_number", ending_block_number)?;
+    }
+
+    match ending_block_number {
+        Some(ending_block_number) if ending_block_number < starting_block_number => {
+            Err(ServerError::InvalidEndingBlockNumber {
+                starting_block_number,
+                ending_block_number,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Parses block number or `YYYY-MM-DD` date
+pub fn parse_block_or_date(value: &str) -> Result<BlockOrDate, ServerError> {
+    if let Ok(block_number) = value.parse::<u64>() {
+        ensure_storable_number("block number", block_number)?;
+        return Ok(BlockOrDate::Block(block_number));
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(BlockOrDate::Date)
+        .map_err(|_| ServerError::InvalidBlockOrDate {
+            value: value.to_string(),
+        })
+}
+
+/// Parses query parameter into value, which deserializes from string, e.g. lowercase enum variant
+pub fn parse_param<'de, T: Deserialize<'de>>(
+    name: &str,
+    value: &'de str,
+) -> Result<T, ServerError> {
+    T::deserialize(StrDeserializer::<ValueError>::new(value)).map_err(|_| {
+        ServerError::InvalidParameter {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    })
+}
+
+/// Parses decimal amount, e.g. `1.5` ether, into integer amount in smallest units (e.g. wei)
+pub fn parse_amount(name: &str, value: &str, decimals: usize) -> Result<U256, ServerError> {
+    let invalid_amount = || ServerError::InvalidParameter {
+        name: name.to_string(),
+        value: value.to_string(),
+    };
+
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+
+    if (integer.is_empty() && fraction.is_empty()) || fraction.len() > decimals {
+        return Err(invalid_amount());
+    }
+
+    // Leading zeros are allowed, digits are checked by parser
+    format!("{integer}{fraction:0<decimals$}")
+        .parse()
+        .map_err(|_| invalid_amount())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_decimal_amounts() {
+        let parse = |value| parse_amount("min_value", value, 18).ok();
+
+        assert_eq!(parse("1"), Some(U256::from(1_000_000_000_000_000_000)));
+        assert_eq!(parse("1.5"), Some(U256::from(1_500_000_000_000_000_000)));
+        assert_eq!(parse(".5"), Some(U256::from(500_000_000_000_000_000)));
+        assert_eq!(parse("2."), Some(U256::from(2_000_000_000_000_000_000)));
+        assert_eq!(parse("0.000000000000000001"), Some(U256::from(1)));
+        assert_eq!(parse("007"), Some(U256::from(7_000_000_000_000_000_000)));
+        assert_eq!(
+            parse_amount("min_value", "15", 0).ok(),
+            Some(U256::from(15))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_amounts() {
+        for value in [
+            "",
+            ".",
+            "-1",
+            "1e18",
+            "1.2.3",
+            "0x10",
+            " 1",
+            // more decimals than unit has
+            "0.0000000000000000001",
+            // more than 2^256 - 1 wei
+            "1000000000000000000000000000000000000000000000000000000000000000000000",
+        ] {
+            assert!(
+                matches!(
+                    parse_amount("min_value", value, 18),
+                    Err(ServerError::InvalidParameter { .. })
+                ),
+                "{value:?} is accepted"
+            );
+        }
+    }
+}
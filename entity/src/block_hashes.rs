@@ -0,0 +1,20 @@
+use crate::unsigned::U64;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Hash of recent block, as it was seen when block was crawled or last re-checked for reorgs.
+/// Blocks without crawled rows are re-checked against it too.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "block_hashes")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub chain_id: U64,
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Unsigned")]
+    pub block_number: U64,
+    pub block_hash: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,43 @@
+use crate::unsigned::U64;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Block interval, which was fully fetched for address from given source.
+/// Intervals of the same address and source never overlap or touch, they are merged on insert.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "crawl_coverage")]
+pub struct Model {
+    /// EIP-155 id of chain, which row was crawled from
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub chain_id: U64,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub address: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub source: CoverageSource,
+    /// First block of interval, inclusive
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Unsigned")]
+    pub start_block: U64,
+    /// Last block of interval, inclusive
+    #[sea_orm(column_type = "Unsigned")]
+    pub end_block: U64,
+}
+
+/// Kind of crawled data, every crawl pass tracks its coverage separately
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(32))")]
+#[serde(rename_all = "snake_case")]
+pub enum CoverageSource {
+    #[sea_orm(string_value = "transactions")]
+    Transactions,
+    #[sea_orm(string_value = "internal_transactions")]
+    InternalTransactions,
+    #[sea_orm(string_value = "token_transfers")]
+    TokenTransfers,
+    #[sea_orm(string_value = "nft_transfers")]
+    NftTransfers,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
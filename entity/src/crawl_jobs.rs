@@ -0,0 +1,52 @@
+use crate::unsigned::U64;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Crawl of an address since starting block, run by background worker
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "crawl_jobs")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// EIP-155 id of chain, which address is crawled on
+    pub chain_id: U64,
+    pub address: String,
+    #[sea_orm(column_type = "Unsigned")]
+    pub starting_block_number: U64,
+    /// Last block to crawl, current block if empty
+    #[sea_orm(column_type = "Unsigned", nullable)]
+    pub ending_block_number: Option<U64>,
+    pub status: CrawlJobStatus,
+    /// Block ranges planned so far, grows as crawl passes are started
+    #[sea_orm(column_type = "Unsigned")]
+    pub ranges_total: U64,
+    #[sea_orm(column_type = "Unsigned")]
+    pub ranges_done: U64,
+    #[sea_orm(column_type = "Unsigned")]
+    pub pages_fetched: U64,
+    #[sea_orm(column_type = "Unsigned")]
+    pub rows_saved: U64,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error: Option<String>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+#[serde(rename_all = "lowercase")]
+pub enum CrawlJobStatus {
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    #[sea_orm(string_value = "running")]
+    Running,
+    #[sea_orm(string_value = "completed")]
+    Completed,
+    #[sea_orm(string_value = "failed")]
+    Failed,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
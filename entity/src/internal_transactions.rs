@@ -0,0 +1,34 @@
+use crate::{u256::U256, unsigned::U64};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Value transfers made by contract calls, identified by parent transaction hash and trace id
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "internal_transactions")]
+pub struct Model {
+    /// EIP-155 id of chain, which row was crawled from
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub chain_id: U64,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub parent_tx_id: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub trace_id: String,
+    #[sea_orm(column_type = "Unsigned")]
+    pub block_number: U64,
+    /// Hash of block at the time of crawl. Explorer doesn't return it, so it's missing
+    /// in new rows, hashes of recent blocks are recorded in block hashes table instead.
+    pub block_hash: Option<String>,
+    #[sea_orm(column_type = "Date")]
+    pub date_time: DateTime,
+    pub address_from: String,
+    pub address_to: String,
+    #[sea_orm(column_type = "String(Some(78))")]
+    pub value: U256,
+    pub tx_type: String,
+    pub is_error: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1 +1,11 @@
+pub mod block_hashes;
+pub mod crawl_coverage;
+pub mod crawl_jobs;
+pub mod internal_transactions;
+pub mod nft_transfers;
+pub mod token_transfers;
+pub mod tokens;
 pub mod transactions;
+pub mod u256;
+pub mod unsigned;
+pub mod watched_addresses;
@@ -0,0 +1,47 @@
+use crate::{u256::U256, unsigned::U64};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// ERC-721 and ERC-1155 token transfers.
+/// Single ERC-1155 batch transfer log moves several token ids, so token id is part of the key.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "nft_transfers")]
+pub struct Model {
+    /// EIP-155 id of chain, which row was crawled from
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub chain_id: U64,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub tx_id: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub log_index: U64,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub token_id: U256,
+    pub standard: NftStandard,
+    #[sea_orm(column_type = "Unsigned")]
+    pub block_number: U64,
+    /// Hash of block at the time of crawl, used to detect reorgs. Missing in older rows.
+    pub block_hash: Option<String>,
+    #[sea_orm(column_type = "Date")]
+    pub date_time: DateTime,
+    pub contract_address: String,
+    pub address_from: String,
+    pub address_to: String,
+    /// Always 1 for ERC-721 transfers
+    #[sea_orm(column_type = "String(Some(78))")]
+    pub quantity: U256,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(8))")]
+#[serde(rename_all = "lowercase")]
+pub enum NftStandard {
+    #[sea_orm(string_value = "erc721")]
+    Erc721,
+    #[sea_orm(string_value = "erc1155")]
+    Erc1155,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
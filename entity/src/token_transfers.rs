@@ -0,0 +1,46 @@
+use crate::{u256::U256, unsigned::U64};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// ERC-20 token transfers, identified by transaction hash and log index
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "token_transfers")]
+pub struct Model {
+    /// EIP-155 id of chain, which row was crawled from
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub chain_id: U64,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub tx_id: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub log_index: U64,
+    #[sea_orm(column_type = "Unsigned")]
+    pub block_number: U64,
+    /// Hash of block at the time of crawl, used to detect reorgs. Missing in older rows.
+    pub block_hash: Option<String>,
+    #[sea_orm(column_type = "Date")]
+    pub date_time: DateTime,
+    pub contract_address: String,
+    pub address_from: String,
+    pub address_to: String,
+    /// Amount in token's smallest units, see `tokens::Model::decimals`
+    #[sea_orm(column_type = "String(Some(78))")]
+    pub amount: U256,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tokens::Entity",
+        from = "(Column::ChainId, Column::ContractAddress)",
+        to = "(super::tokens::Column::ChainId, super::tokens::Column::ContractAddress)"
+    )]
+    Tokens,
+}
+
+impl Related<super::tokens::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tokens.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,31 @@
+use crate::unsigned::{U32, U64};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// ERC-20 token metadata, as reported by explorer along with token transfers
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "tokens")]
+pub struct Model {
+    /// EIP-155 id of chain, which row was crawled from
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub chain_id: U64,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub contract_address: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: U32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::token_transfers::Entity")]
+    TokenTransfers,
+}
+
+impl Related<super::token_transfers::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TokenTransfers.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
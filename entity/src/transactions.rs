@@ -1,21 +1,111 @@
+use crate::{
+    u256::U256,
+    unsigned::{U64, U8},
+};
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
 #[sea_orm(table_name = "transactions")]
 pub struct Model {
-    #[sea_orm(primary_key)]
+    /// EIP-155 id of chain, which row was crawled from
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub chain_id: U64,
+    #[sea_orm(primary_key, auto_increment = false)]
     pub tx_id: String,
     #[sea_orm(column_type = "Unsigned")]
-    pub block_number: u64,
+    pub block_number: U64,
+    /// Hash of including block, used to detect reorgs.
+    /// Empty for transactions crawled before it was captured.
+    pub block_hash: Option<String>,
     #[sea_orm(column_type = "Date")]
     pub date_time: DateTime,
     pub address_from: String,
-    pub address_to: String,
-    #[sea_orm(column_type = "Unsigned")]
-    pub value: u64,
-    #[sea_orm(column_type = "Unsigned")]
-    pub tx_fee: u64,
+    /// Empty for contract creations
+    pub address_to: Option<String>,
+    #[sea_orm(column_type = "String(Some(78))")]
+    pub value: U256,
+    #[sea_orm(column_type = "String(Some(78))")]
+    pub tx_fee: U256,
+    /// Address of contract, created by this transaction
+    pub contract_address: Option<String>,
+    pub is_error: bool,
+    /// Receipt status, unknown for transactions before Byzantium fork
+    pub receipt_status: Option<bool>,
+    // Fields below are empty for transactions crawled before they were captured
+    #[sea_orm(column_type = "Unsigned", nullable)]
+    pub nonce: Option<U64>,
+    /// Position of transaction within its block
+    #[sea_orm(column_type = "Unsigned", nullable)]
+    pub transaction_index: Option<U64>,
+    /// Gas limit
+    #[sea_orm(column_type = "Unsigned", nullable)]
+    pub gas: Option<U64>,
+    #[sea_orm(column_type = "Unsigned", nullable)]
+    pub gas_used: Option<U64>,
+    #[sea_orm(column_type = "String(Some(78))", nullable)]
+    pub gas_price: Option<U256>,
+    #[sea_orm(column_type = "Unsigned", nullable)]
+    pub cumulative_gas_used: Option<U64>,
+    /// Hex encoded call data
+    #[sea_orm(column_type = "Text", nullable)]
+    pub input: Option<String>,
+    /// Selector of called method, `0x` for plain transfers
+    pub method_id: Option<String>,
+    /// Signature of called method, if explorer knows it
+    #[sea_orm(column_type = "Text", nullable)]
+    pub function_name: Option<String>,
+    // Fee breakdown, filled from node receipts and blocks
+    /// 0 for legacy, 1 for access list, 2 for EIP-1559 and 3 for EIP-4844 blob transactions
+    pub tx_type: Option<U8>,
+    #[sea_orm(column_type = "String(Some(78))", nullable)]
+    pub max_fee_per_gas: Option<U256>,
+    #[sea_orm(column_type = "String(Some(78))", nullable)]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[sea_orm(column_type = "String(Some(78))", nullable)]
+    pub effective_gas_price: Option<U256>,
+    /// Base fee of block, empty before London fork
+    #[sea_orm(column_type = "String(Some(78))", nullable)]
+    pub base_fee_per_gas: Option<U256>,
+    #[sea_orm(column_type = "Unsigned", nullable)]
+    pub blob_gas_used: Option<U64>,
+    #[sea_orm(column_type = "String(Some(78))", nullable)]
+    pub blob_gas_price: Option<U256>,
+}
+
+impl Model {
+    /// Recipient of transaction value: called address or created contract
+    pub fn recipient(&self) -> Option<&str> {
+        self.address_to
+            .as_deref()
+            .or(self.contract_address.as_deref())
+    }
+
+    /// Failed transactions don't move value, but their fee is still charged
+    pub fn is_failed(&self) -> bool {
+        self.is_error || self.receipt_status == Some(false)
+    }
+
+    /// Burnt part of fee: base fee and blob fee. Unknown until fee breakdown is filled.
+    pub fn burnt_fee(&self) -> Option<U256> {
+        self.effective_gas_price?;
+
+        let base_fee =
+            U256::from(self.gas_used?).checked_mul(self.base_fee_per_gas.unwrap_or_default())?;
+        let blob_fee = U256::from(self.blob_gas_used.unwrap_or_default())
+            .checked_mul(self.blob_gas_price.unwrap_or_default())?;
+
+        base_fee.checked_add(blob_fee)
+    }
+
+    /// Part of fee, paid to block producer as priority tip
+    pub fn priority_fee(&self) -> Option<U256> {
+        let tip_per_gas = self
+            .effective_gas_price?
+            .checked_sub(self.base_fee_per_gas.unwrap_or_default())?;
+
+        U256::from(self.gas_used?).checked_mul(tip_per_gas)
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
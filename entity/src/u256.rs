@@ -0,0 +1,273 @@
+use crate::unsigned::U64;
+use sea_orm::{
+    sea_query::{ArrayType, ColumnType, Nullable, ValueType, ValueTypeErr},
+    ColIdx, DbErr, QueryResult, TryFromU64, TryGetError, TryGetable, Value,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, iter::Sum, ops, str::FromStr};
+
+pub use primitive_types::U256 as RawU256;
+
+/// 256-bit unsigned integer, used for wei and token amounts.
+///
+/// Stored as zero padded decimal string of `U256::DIGITS` length,
+/// so that string comparison and ordering on database side match numeric ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct U256(pub RawU256);
+
+impl U256 {
+    /// Number of decimal digits in `U256::MAX`
+    pub const DIGITS: u32 = 78;
+
+    pub fn zero() -> Self {
+        Self(RawU256::zero())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        self.0.checked_mul(other.0).map(Self)
+    }
+
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+
+    /// Zero padded representation, used for storage.
+    /// Padding is applied to formatted string, because `RawU256` ignores width when formatting zero.
+    pub fn to_padded_string(&self) -> String {
+        format!(
+            "{:0>width$}",
+            self.0.to_string(),
+            width = Self::DIGITS as usize
+        )
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(value: u64) -> Self {
+        Self(RawU256::from(value))
+    }
+}
+
+impl From<U64> for U256 {
+    fn from(value: U64) -> Self {
+        Self::from(value.0)
+    }
+}
+
+impl From<RawU256> for U256 {
+    fn from(value: RawU256) -> Self {
+        Self(value)
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseU256Error;
+
+impl fmt::Display for ParseU256Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid 256-bit unsigned integer")
+    }
+}
+
+impl std::error::Error for ParseU256Error {}
+
+impl FromStr for U256 {
+    type Err = ParseU256Error;
+
+    /// Parses decimal string, leading zeros are allowed
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseU256Error);
+        }
+        RawU256::from_dec_str(s)
+            .map(Self)
+            .map_err(|_| ParseU256Error)
+    }
+}
+
+impl ops::Add for U256 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl ops::AddAssign for U256 {
+    fn add_assign(&mut self, other: Self) {
+        self.0 += other.0
+    }
+}
+
+impl ops::Sub for U256 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0)
+    }
+}
+
+impl Sum for U256 {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, value| acc + value)
+    }
+}
+
+impl Serialize for U256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for U256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct U256Visitor;
+
+        impl<'de> de::Visitor<'de> for U256Visitor {
+            type Value = U256;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("decimal string or unsigned integer")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<U256, E> {
+                Ok(U256::from(value))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<U256, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(U256Visitor)
+    }
+}
+
+impl From<U256> for Value {
+    fn from(value: U256) -> Self {
+        Value::String(Some(Box::new(value.to_padded_string())))
+    }
+}
+
+impl ValueType for U256 {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::String(Some(value)) => value.parse().map_err(|_| ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        stringify!(U256).to_owned()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::String(Some(Self::DIGITS))
+    }
+}
+
+impl Nullable for U256 {
+    fn null() -> Value {
+        Value::String(None)
+    }
+}
+
+impl TryGetable for U256 {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value = String::try_get_by(res, index)?;
+        value
+            .parse()
+            .map_err(|e: ParseU256Error| TryGetError::DbErr(DbErr::Type(format!("{e}: {value:?}"))))
+    }
+}
+
+impl TryFromU64 for U256 {
+    fn try_from_u64(value: u64) -> Result<Self, DbErr> {
+        Ok(Self::from(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX: &str =
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+    #[test]
+    fn parses_decimal_strings() {
+        assert_eq!("0".parse(), Ok(U256::zero()));
+        assert_eq!("000123".parse(), Ok(U256::from(123)));
+        assert_eq!(MAX.parse::<U256>().map(|max| max.0), Ok(RawU256::MAX));
+
+        assert_eq!("".parse::<U256>(), Err(ParseU256Error));
+        assert_eq!("-1".parse::<U256>(), Err(ParseU256Error));
+        assert_eq!("+1".parse::<U256>(), Err(ParseU256Error));
+        assert_eq!("0x10".parse::<U256>(), Err(ParseU256Error));
+        assert_eq!("1.5".parse::<U256>(), Err(ParseU256Error));
+        // 2^256
+        assert_eq!(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+                .parse::<U256>(),
+            Err(ParseU256Error)
+        );
+    }
+
+    #[test]
+    fn formats_plain_and_padded() {
+        assert_eq!(U256::from(42).to_string(), "42");
+        assert_eq!(U256(RawU256::MAX).to_string(), MAX);
+
+        assert_eq!(U256::zero().to_padded_string(), "0".repeat(78));
+        assert_eq!(
+            U256::from(42).to_padded_string(),
+            format!("{}42", "0".repeat(76))
+        );
+        assert_eq!(U256(RawU256::MAX).to_padded_string(), MAX);
+    }
+
+    #[test]
+    fn padded_strings_sort_as_numbers() {
+        let mut amounts = [
+            U256::from(1000),
+            U256::from(9),
+            U256(RawU256::MAX),
+            U256::zero(),
+        ];
+        let mut padded: Vec<String> = amounts.iter().map(U256::to_padded_string).collect();
+
+        amounts.sort();
+        padded.sort();
+
+        assert_eq!(
+            padded,
+            amounts
+                .iter()
+                .map(U256::to_padded_string)
+                .collect::<Vec<_>>()
+        );
+        assert!(padded.iter().all(|padded| padded.parse::<U256>().is_ok()));
+    }
+}
@@ -0,0 +1,127 @@
+use sea_orm::{
+    sea_query::{ArrayType, ColumnType, Nullable, ValueType, ValueTypeErr},
+    ColIdx, DbErr, QueryResult, TryFromU64, TryGetError, TryGetable, Value,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, num::ParseIntError, str::FromStr};
+
+/// Defines unsigned integer, stored in an unsigned column on MySQL
+/// and in a signed column of the same width on PostgreSQL and SQLite,
+/// which lack unsigned types.
+macro_rules! unsigned {
+    ($(#[$meta:meta])* $name:ident, $unsigned:ty, $signed:ty, $variant:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name(pub $unsigned);
+
+        impl From<$unsigned> for $name {
+            fn from(value: $unsigned) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for $unsigned {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map(Self)
+            }
+        }
+
+        impl From<$name> for Value {
+            fn from(value: $name) -> Self {
+                Value::$variant(Some(value.0))
+            }
+        }
+
+        impl ValueType for $name {
+            fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+                <$unsigned as ValueType>::try_from(v).map(Self)
+            }
+
+            fn type_name() -> String {
+                stringify!($name).to_owned()
+            }
+
+            fn array_type() -> ArrayType {
+                ArrayType::$variant
+            }
+
+            fn column_type() -> ColumnType {
+                ColumnType::$variant
+            }
+        }
+
+        impl Nullable for $name {
+            fn null() -> Value {
+                Value::$variant(None)
+            }
+        }
+
+        impl TryGetable for $name {
+            /// Reads unsigned value on MySQL, falls back to signed one on other backends
+            fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+                match <$unsigned>::try_get_by(res, index) {
+                    Ok(value) => Ok(Self(value)),
+                    Err(TryGetError::DbErr(_)) => {
+                        let value = <$signed>::try_get_by(res, index)?;
+                        <$unsigned as TryFrom<_>>::try_from(value).map(Self).map_err(|_| {
+                            TryGetError::DbErr(DbErr::Type(format!(
+                                "{value} is out of {} range",
+                                stringify!($name)
+                            )))
+                        })
+                    }
+                    Err(err) => Err(err),
+                }
+            }
+        }
+
+        impl TryFromU64 for $name {
+            fn try_from_u64(value: u64) -> Result<Self, DbErr> {
+                <$unsigned as TryFrom<_>>::try_from(value)
+                    .map(Self)
+                    .map_err(|e| DbErr::Type(e.to_string()))
+            }
+        }
+    };
+}
+
+unsigned!(
+    /// 64-bit unsigned integer, used for block numbers, gas and counters
+    U64,
+    u64,
+    i64,
+    BigUnsigned
+);
+
+unsigned!(
+    /// 32-bit unsigned integer, used for token decimals
+    U32,
+    u32,
+    i32,
+    Unsigned
+);
+
+unsigned!(
+    /// 8-bit unsigned integer, used for transaction type
+    U8,
+    u8,
+    i16,
+    TinyUnsigned
+);
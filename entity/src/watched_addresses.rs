@@ -0,0 +1,39 @@
+use crate::unsigned::U64;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Address, which is crawled up to current block on schedule
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "watched_addresses")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// EIP-155 id of chain, which address is watched on. Address is unique within chain.
+    pub chain_id: U64,
+    pub address: String,
+    #[sea_orm(column_type = "Unsigned")]
+    pub starting_block_number: U64,
+    pub status: WatchStatus,
+    /// Latest crawl job, started by scheduler
+    #[sea_orm(nullable)]
+    pub last_crawl_job_id: Option<i32>,
+    #[sea_orm(nullable)]
+    pub last_crawled_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+#[serde(rename_all = "lowercase")]
+pub enum WatchStatus {
+    #[sea_orm(string_value = "active")]
+    Active,
+    #[sea_orm(string_value = "paused")]
+    Paused,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
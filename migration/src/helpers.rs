@@ -0,0 +1,116 @@
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{ConnectionTrait, TransactionTrait},
+};
+
+/// Number of decimal digits in 2^256 - 1, length of columns, which store U256 amounts
+pub const U256_DIGITS: u32 = 78;
+
+/// Replaces column with a new one, defined by `define`, and fills it with `value`.
+///
+/// Portable alternative of `modify_column`, which SQLite doesn't support at all
+/// and PostgreSQL doesn't support for conversions without implicit casts.
+pub async fn replace_column(
+    manager: &SchemaManager<'_>,
+    table: impl IntoIden,
+    column: impl IntoIden,
+    define: impl FnOnce(&mut ColumnDef) -> &mut ColumnDef,
+    value: SimpleExpr,
+) -> Result<(), DbErr> {
+    let table = table.into_iden();
+    let column = column.into_iden();
+    let replacement = Alias::new(format!("{}_replacement", column.to_string()));
+
+    manager
+        .alter_table(
+            Table::alter()
+                .table(table.clone())
+                .add_column(define(&mut ColumnDef::new(replacement.clone())))
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .exec_stmt(
+            Query::update()
+                .table(table.clone())
+                .value(replacement.clone(), value)
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .alter_table(
+            Table::alter()
+                .table(table.clone())
+                .drop_column(column.clone())
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .alter_table(
+            Table::alter()
+                .table(table)
+                .rename_column(replacement, column)
+                .to_owned(),
+        )
+        .await
+}
+
+/// Replaces table with a new one, defined by `create`, and copies rows matching `condition` over.
+/// Columns, which are given in `values`, are filled with their expressions,
+/// the rest are copied from columns of the same name.
+///
+/// Portable way to change primary and unique keys, which SQLite can't alter in place.
+pub async fn rebuild_table(
+    manager: &SchemaManager<'_>,
+    table: impl IntoIden,
+    mut create: TableCreateStatement,
+    values: Vec<(DynIden, SimpleExpr)>,
+    condition: Condition,
+) -> Result<(), DbErr> {
+    let table = table.into_iden();
+    let rebuilt = Alias::new(format!("{}_rebuilt", table.to_string()));
+    let backend = manager.get_database_backend();
+
+    // Steps share one connection, other pooled SQLite connections may still see the dropped table
+    let txn = manager.get_connection().begin().await?;
+
+    txn.execute(backend.build(create.table(rebuilt.clone())))
+        .await?;
+
+    let mut columns: Vec<DynIden> = vec![];
+    let mut select = Query::select();
+
+    for column in create.get_columns() {
+        let column = Alias::new(column.get_column_name()).into_iden();
+        let value = values
+            .iter()
+            .find(|(valued, _)| valued.to_string() == column.to_string())
+            .map(|(_, value)| value.clone())
+            .unwrap_or_else(|| Expr::col(column.clone()).into());
+
+        select.expr(value);
+        columns.push(column);
+    }
+
+    txn.execute(
+        backend.build(
+            Query::insert()
+                .into_table(rebuilt.clone())
+                .columns(columns)
+                .select_from(select.from(table.clone()).cond_where(condition).to_owned())
+                .map_err(|err| DbErr::Custom(err.to_string()))?,
+        ),
+    )
+    .await?;
+
+    txn.execute(backend.build(Table::drop().table(table.clone())))
+        .await?;
+
+    txn.execute(backend.build(Table::rename().table(rebuilt, table)))
+        .await?;
+
+    txn.commit().await
+}
@@ -1,14 +1,49 @@
 pub use sea_orm_migration::prelude::*;
 
+mod helpers;
 mod m20230913_132440_create_transactions_table;
+mod m20230921_101500_store_wei_as_u256;
+mod m20230925_143000_create_internal_transactions_table;
+mod m20230928_090000_create_token_transfers_and_tokens_tables;
+mod m20231002_120000_create_nft_transfers_table;
+mod m20231009_161000_create_crawl_jobs_table;
+mod m20231012_094500_create_crawl_coverage_table;
+mod m20231016_100000_add_contract_creation_and_status_to_transactions;
+mod m20231018_113000_add_gas_and_input_to_transactions;
+mod m20231020_090000_add_fee_breakdown_to_transactions;
+mod m20231023_100000_create_watched_addresses_table;
+mod m20231025_090000_add_block_hash_to_transactions;
+mod m20231027_090000_add_ending_block_number_to_crawl_jobs;
+mod m20231030_090000_add_chain_id;
+mod m20231101_090000_add_block_hash_to_transfers;
+mod m20231105_090000_pad_zero_amounts;
+mod m20231107_090000_create_block_hashes_table;
+mod m20231108_090000_add_address_indexes;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(
-            m20230913_132440_create_transactions_table::Migration,
-        )]
+        vec![
+            Box::new(m20230913_132440_create_transactions_table::Migration),
+            Box::new(m20230921_101500_store_wei_as_u256::Migration),
+            Box::new(m20230925_143000_create_internal_transactions_table::Migration),
+            Box::new(m20230928_090000_create_token_transfers_and_tokens_tables::Migration),
+            Box::new(m20231002_120000_create_nft_transfers_table::Migration),
+            Box::new(m20231009_161000_create_crawl_jobs_table::Migration),
+            Box::new(m20231012_094500_create_crawl_coverage_table::Migration),
+            Box::new(m20231016_100000_add_contract_creation_and_status_to_transactions::Migration),
+            Box::new(m20231018_113000_add_gas_and_input_to_transactions::Migration),
+            Box::new(m20231020_090000_add_fee_breakdown_to_transactions::Migration),
+            Box::new(m20231023_100000_create_watched_addresses_table::Migration),
+            Box::new(m20231025_090000_add_block_hash_to_transactions::Migration),
+            Box::new(m20231027_090000_add_ending_block_number_to_crawl_jobs::Migration),
+            Box::new(m20231030_090000_add_chain_id::Migration),
+            Box::new(m20231101_090000_add_block_hash_to_transfers::Migration),
+            Box::new(m20231105_090000_pad_zero_amounts::Migration),
+            Box::new(m20231107_090000_create_block_hashes_table::Migration),
+            Box::new(m20231108_090000_add_address_indexes::Migration),
+        ]
     }
 }
@@ -0,0 +1,102 @@
+use crate::helpers::{replace_column, U256_DIGITS};
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [Transactions::Value, Transactions::TxFee] {
+            if manager.get_database_backend() == DbBackend::Sqlite {
+                // SQLite can't modify columns, so padded values are copied into a new one
+                replace_column(
+                    manager,
+                    Transactions::Table,
+                    column.clone(),
+                    |definition| definition.string_len(U256_DIGITS).not_null().default(""),
+                    Func::cust(Alias::new("printf"))
+                        .args([
+                            Expr::val(format!("%0{U256_DIGITS}d")).into(),
+                            Expr::col(column).into(),
+                        ])
+                        .into(),
+                )
+                .await?;
+
+                continue;
+            }
+
+            // Existing integers are converted to their decimal representation
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Transactions::Table)
+                        .modify_column(
+                            ColumnDef::new(column.clone())
+                                .string_len(U256_DIGITS)
+                                .not_null(),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+
+            // Left pad them with zeros, so that string ordering matches numeric one
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(Transactions::Table)
+                        .value(
+                            column.clone(),
+                            Func::cust(Alias::new("LPAD")).args([
+                                Expr::col(column).into(),
+                                // PostgreSQL has no LPAD overload with bigint length
+                                Expr::val(U256_DIGITS as i32).into(),
+                                Expr::val("0").into(),
+                            ]),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Amounts above u64::MAX can't be converted back and make this migration fail
+        for column in [Transactions::Value, Transactions::TxFee] {
+            if manager.get_database_backend() == DbBackend::MySql {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(Transactions::Table)
+                            .modify_column(ColumnDef::new(column).big_unsigned().not_null())
+                            .to_owned(),
+                    )
+                    .await?;
+
+                continue;
+            }
+
+            // Strings aren't implicitly cast to integers outside of MySQL
+            replace_column(
+                manager,
+                Transactions::Table,
+                column.clone(),
+                |definition| definition.big_unsigned().not_null().default(0),
+                Expr::col(column).cast_as(Alias::new("BIGINT")),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden, Clone)]
+enum Transactions {
+    Table,
+    Value,
+    TxFee,
+}
@@ -0,0 +1,89 @@
+use crate::helpers::U256_DIGITS;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(InternalTransactions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(InternalTransactions::ParentTxId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InternalTransactions::TraceId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InternalTransactions::BlockNumber)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InternalTransactions::DateTime)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InternalTransactions::AddressFrom)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InternalTransactions::AddressTo)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InternalTransactions::Value)
+                            .string_len(U256_DIGITS)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InternalTransactions::TxType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InternalTransactions::IsError)
+                            .boolean()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(InternalTransactions::ParentTxId)
+                            .col(InternalTransactions::TraceId),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(InternalTransactions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum InternalTransactions {
+    Table,
+    ParentTxId,
+    TraceId,
+    BlockNumber,
+    DateTime,
+    AddressFrom,
+    AddressTo,
+    Value,
+    TxType,
+    IsError,
+}
@@ -0,0 +1,110 @@
+use crate::helpers::U256_DIGITS;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tokens::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Tokens::ContractAddress)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Tokens::Name).string().not_null())
+                    .col(ColumnDef::new(Tokens::Symbol).string().not_null())
+                    .col(ColumnDef::new(Tokens::Decimals).unsigned().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TokenTransfers::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(TokenTransfers::TxId).string().not_null())
+                    .col(
+                        ColumnDef::new(TokenTransfers::LogIndex)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TokenTransfers::BlockNumber)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TokenTransfers::DateTime)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TokenTransfers::ContractAddress)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TokenTransfers::AddressFrom)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TokenTransfers::AddressTo)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TokenTransfers::Amount)
+                            .string_len(U256_DIGITS)
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(TokenTransfers::TxId)
+                            .col(TokenTransfers::LogIndex),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TokenTransfers::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Tokens::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Tokens {
+    Table,
+    ContractAddress,
+    Name,
+    Symbol,
+    Decimals,
+}
+
+#[derive(DeriveIden)]
+enum TokenTransfers {
+    Table,
+    TxId,
+    LogIndex,
+    BlockNumber,
+    DateTime,
+    ContractAddress,
+    AddressFrom,
+    AddressTo,
+    Amount,
+}
@@ -0,0 +1,88 @@
+use crate::helpers::U256_DIGITS;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NftTransfers::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(NftTransfers::TxId).string().not_null())
+                    .col(
+                        ColumnDef::new(NftTransfers::LogIndex)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NftTransfers::TokenId)
+                            .string_len(U256_DIGITS)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NftTransfers::Standard)
+                            .string_len(8)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NftTransfers::BlockNumber)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NftTransfers::DateTime)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NftTransfers::ContractAddress)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NftTransfers::AddressFrom)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(NftTransfers::AddressTo).string().not_null())
+                    .col(
+                        ColumnDef::new(NftTransfers::Quantity)
+                            .string_len(U256_DIGITS)
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(NftTransfers::TxId)
+                            .col(NftTransfers::LogIndex)
+                            .col(NftTransfers::TokenId),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NftTransfers::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NftTransfers {
+    Table,
+    TxId,
+    LogIndex,
+    TokenId,
+    Standard,
+    BlockNumber,
+    DateTime,
+    ContractAddress,
+    AddressFrom,
+    AddressTo,
+    Quantity,
+}
@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CrawlJobs::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CrawlJobs::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CrawlJobs::Address).string().not_null())
+                    .col(
+                        ColumnDef::new(CrawlJobs::StartingBlockNumber)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CrawlJobs::Status).string_len(16).not_null())
+                    .col(
+                        ColumnDef::new(CrawlJobs::RangesTotal)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CrawlJobs::RangesDone)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CrawlJobs::PagesFetched)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CrawlJobs::RowsSaved)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CrawlJobs::Error).text())
+                    .col(ColumnDef::new(CrawlJobs::CreatedAt).date_time().not_null())
+                    .col(ColumnDef::new(CrawlJobs::UpdatedAt).date_time().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CrawlJobs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CrawlJobs {
+    Table,
+    Id,
+    Address,
+    StartingBlockNumber,
+    Status,
+    RangesTotal,
+    RangesDone,
+    PagesFetched,
+    RowsSaved,
+    Error,
+    CreatedAt,
+    UpdatedAt,
+}
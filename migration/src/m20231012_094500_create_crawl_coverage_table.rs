@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CrawlCoverage::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(CrawlCoverage::Address).string().not_null())
+                    .col(
+                        ColumnDef::new(CrawlCoverage::Source)
+                            .string_len(32)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CrawlCoverage::StartBlock)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CrawlCoverage::EndBlock)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(CrawlCoverage::Address)
+                            .col(CrawlCoverage::Source)
+                            .col(CrawlCoverage::StartBlock),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CrawlCoverage::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CrawlCoverage {
+    Table,
+    Address,
+    Source,
+    StartBlock,
+    EndBlock,
+}
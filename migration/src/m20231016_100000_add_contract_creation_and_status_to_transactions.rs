@@ -0,0 +1,133 @@
+use crate::helpers::replace_column;
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Contract creations have no recipient
+        if manager.get_database_backend() == DbBackend::Sqlite {
+            replace_column(
+                manager,
+                Transactions::Table,
+                Transactions::AddressTo,
+                |definition| definition.string().null(),
+                Expr::col(Transactions::AddressTo).into(),
+            )
+            .await?;
+        } else {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Transactions::Table)
+                        .modify_column(ColumnDef::new(Transactions::AddressTo).string().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Transactions::Table)
+                    .value(Transactions::AddressTo, Option::<String>::None)
+                    .and_where(Expr::col(Transactions::AddressTo).eq(""))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column(
+                        ColumnDef::new(Transactions::ContractAddress)
+                            .string()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column(
+                        ColumnDef::new(Transactions::IsError)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column(ColumnDef::new(Transactions::ReceiptStatus).boolean().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            Transactions::ReceiptStatus,
+            Transactions::IsError,
+            Transactions::ContractAddress,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Transactions::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Transactions::Table)
+                    .value(Transactions::AddressTo, "")
+                    .and_where(Expr::col(Transactions::AddressTo).is_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        if manager.get_database_backend() == DbBackend::Sqlite {
+            return replace_column(
+                manager,
+                Transactions::Table,
+                Transactions::AddressTo,
+                |definition| definition.string().not_null().default(""),
+                Expr::col(Transactions::AddressTo).into(),
+            )
+            .await;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .modify_column(ColumnDef::new(Transactions::AddressTo).string().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    AddressTo,
+    ContractAddress,
+    IsError,
+    ReceiptStatus,
+}
@@ -0,0 +1,99 @@
+use crate::helpers::U256_DIGITS;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Nullable, transactions crawled so far don't have these fields
+        let columns = [
+            ColumnDef::new(Transactions::Nonce)
+                .big_unsigned()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::TransactionIndex)
+                .big_unsigned()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::Gas)
+                .big_unsigned()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::GasUsed)
+                .big_unsigned()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::GasPrice)
+                .string_len(U256_DIGITS)
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::CumulativeGasUsed)
+                .big_unsigned()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::Input).text().null().to_owned(),
+            ColumnDef::new(Transactions::MethodId)
+                .string()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::FunctionName)
+                .text()
+                .null()
+                .to_owned(),
+        ];
+
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Transactions::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            Transactions::FunctionName,
+            Transactions::MethodId,
+            Transactions::Input,
+            Transactions::CumulativeGasUsed,
+            Transactions::GasPrice,
+            Transactions::GasUsed,
+            Transactions::Gas,
+            Transactions::TransactionIndex,
+            Transactions::Nonce,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Transactions::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    Nonce,
+    TransactionIndex,
+    Gas,
+    GasUsed,
+    GasPrice,
+    CumulativeGasUsed,
+    Input,
+    MethodId,
+    FunctionName,
+}
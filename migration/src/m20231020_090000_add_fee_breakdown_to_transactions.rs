@@ -0,0 +1,89 @@
+use crate::helpers::U256_DIGITS;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            ColumnDef::new(Transactions::TxType)
+                .tiny_unsigned()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::MaxFeePerGas)
+                .string_len(U256_DIGITS)
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::MaxPriorityFeePerGas)
+                .string_len(U256_DIGITS)
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::EffectiveGasPrice)
+                .string_len(U256_DIGITS)
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::BaseFeePerGas)
+                .string_len(U256_DIGITS)
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::BlobGasUsed)
+                .big_unsigned()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Transactions::BlobGasPrice)
+                .string_len(U256_DIGITS)
+                .null()
+                .to_owned(),
+        ];
+
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Transactions::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            Transactions::BlobGasPrice,
+            Transactions::BlobGasUsed,
+            Transactions::BaseFeePerGas,
+            Transactions::EffectiveGasPrice,
+            Transactions::MaxPriorityFeePerGas,
+            Transactions::MaxFeePerGas,
+            Transactions::TxType,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Transactions::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    TxType,
+    MaxFeePerGas,
+    MaxPriorityFeePerGas,
+    EffectiveGasPrice,
+    BaseFeePerGas,
+    BlobGasUsed,
+    BlobGasPrice,
+}
@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WatchedAddresses::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WatchedAddresses::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(WatchedAddresses::Address)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(WatchedAddresses::StartingBlockNumber)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WatchedAddresses::Status)
+                            .string_len(16)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(WatchedAddresses::LastCrawlJobId).integer())
+                    .col(ColumnDef::new(WatchedAddresses::LastCrawledAt).date_time())
+                    .col(
+                        ColumnDef::new(WatchedAddresses::CreatedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WatchedAddresses::UpdatedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WatchedAddresses::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WatchedAddresses {
+    Table,
+    Id,
+    Address,
+    StartingBlockNumber,
+    Status,
+    LastCrawlJobId,
+    LastCrawledAt,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Nullable, transactions crawled so far don't have block hash
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column(ColumnDef::new(Transactions::BlockHash).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .drop_column(Transactions::BlockHash)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    BlockHash,
+}
@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Nullable, jobs without ending block crawl up to current block
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CrawlJobs::Table)
+                    .add_column(
+                        ColumnDef::new(CrawlJobs::EndingBlockNumber)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CrawlJobs::Table)
+                    .drop_column(CrawlJobs::EndingBlockNumber)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CrawlJobs {
+    Table,
+    EndingBlockNumber,
+}
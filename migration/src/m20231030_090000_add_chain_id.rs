@@ -0,0 +1,613 @@
+use crate::helpers::{rebuild_table, U256_DIGITS};
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+/// Chain of rows, which were crawled before chains were tracked
+const MAINNET_CHAIN_ID: u64 = 1;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Chain id becomes part of primary and unique keys, so tables are rebuilt
+        for (table, create) in tables(true) {
+            rebuild_table(
+                manager,
+                table,
+                create,
+                vec![(
+                    Chain::ChainId.into_iden(),
+                    Expr::val(MAINNET_CHAIN_ID).into(),
+                )],
+                Condition::all(),
+            )
+            .await?;
+        }
+
+        reset_watched_addresses_sequence(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CrawlJobs::Table)
+                    .add_column(
+                        ColumnDef::new(Chain::ChainId)
+                            .big_unsigned()
+                            .not_null()
+                            .default(MAINNET_CHAIN_ID),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Rows of other chains would clash on keys without chain id, so only mainnet is kept
+        let mainnet = || Condition::all().add(Expr::col(Chain::ChainId).eq(MAINNET_CHAIN_ID));
+
+        for (table, create) in tables(false) {
+            rebuild_table(manager, table, create, vec![], mainnet()).await?;
+        }
+
+        reset_watched_addresses_sequence(manager).await?;
+
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(CrawlJobs::Table)
+                    .cond_where(mainnet().not())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CrawlJobs::Table)
+                    .drop_column(Chain::ChainId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Copied ids don't advance PostgreSQL sequence of rebuilt table, so it's moved past them
+async fn reset_watched_addresses_sequence(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    if manager.get_database_backend() != DbBackend::Postgres {
+        return Ok(());
+    }
+
+    manager
+        .get_connection()
+        .execute_unprepared(
+            "SELECT setval(pg_get_serial_sequence('watched_addresses', 'id'), \
+             COALESCE(MAX(id), 0) + 1, false) FROM watched_addresses",
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Definitions of tables, keyed by chain id or not
+fn tables(chain_id: bool) -> Vec<(DynIden, TableCreateStatement)> {
+    vec![
+        (
+            Transactions::Table.into_iden(),
+            transactions(chain_id).to_owned(),
+        ),
+        (
+            InternalTransactions::Table.into_iden(),
+            internal_transactions(chain_id).to_owned(),
+        ),
+        (Tokens::Table.into_iden(), tokens(chain_id).to_owned()),
+        (
+            TokenTransfers::Table.into_iden(),
+            token_transfers(chain_id).to_owned(),
+        ),
+        (
+            NftTransfers::Table.into_iden(),
+            nft_transfers(chain_id).to_owned(),
+        ),
+        (
+            CrawlCoverage::Table.into_iden(),
+            crawl_coverage(chain_id).to_owned(),
+        ),
+        (
+            WatchedAddresses::Table.into_iden(),
+            watched_addresses(chain_id).to_owned(),
+        ),
+    ]
+}
+
+/// Table, which starts with chain id column if it's keyed by chain
+fn table(chain_id: bool) -> TableCreateStatement {
+    let mut table = Table::create();
+
+    if chain_id {
+        table.col(ColumnDef::new(Chain::ChainId).big_unsigned().not_null());
+    }
+
+    table
+}
+
+/// Key of given columns, prefixed by chain id if table is keyed by chain
+fn key(chain_id: bool, columns: impl IntoIterator<Item = DynIden>) -> IndexCreateStatement {
+    let mut key = Index::create();
+
+    if chain_id {
+        key.col(Chain::ChainId);
+    }
+    for column in columns {
+        key.col(column);
+    }
+
+    key
+}
+
+fn transactions(chain_id: bool) -> TableCreateStatement {
+    table(chain_id)
+        .col(ColumnDef::new(Transactions::TxId).string().not_null())
+        .col(
+            ColumnDef::new(Transactions::BlockNumber)
+                .big_unsigned()
+                .not_null(),
+        )
+        .col(ColumnDef::new(Transactions::BlockHash).string().null())
+        .col(
+            ColumnDef::new(Transactions::DateTime)
+                .date_time()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(Transactions::AddressFrom)
+                .string()
+                .not_null(),
+        )
+        .col(ColumnDef::new(Transactions::AddressTo).string().null())
+        .col(
+            ColumnDef::new(Transactions::Value)
+                .string_len(U256_DIGITS)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(Transactions::TxFee)
+                .string_len(U256_DIGITS)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(Transactions::ContractAddress)
+                .string()
+                .null(),
+        )
+        .col(
+            ColumnDef::new(Transactions::IsError)
+                .boolean()
+                .not_null()
+                .default(false),
+        )
+        .col(ColumnDef::new(Transactions::ReceiptStatus).boolean().null())
+        .col(ColumnDef::new(Transactions::Nonce).big_unsigned().null())
+        .col(
+            ColumnDef::new(Transactions::TransactionIndex)
+                .big_unsigned()
+                .null(),
+        )
+        .col(ColumnDef::new(Transactions::Gas).big_unsigned().null())
+        .col(ColumnDef::new(Transactions::GasUsed).big_unsigned().null())
+        .col(
+            ColumnDef::new(Transactions::GasPrice)
+                .string_len(U256_DIGITS)
+                .null(),
+        )
+        .col(
+            ColumnDef::new(Transactions::CumulativeGasUsed)
+                .big_unsigned()
+                .null(),
+        )
+        .col(ColumnDef::new(Transactions::Input).text().null())
+        .col(ColumnDef::new(Transactions::MethodId).string().null())
+        .col(ColumnDef::new(Transactions::FunctionName).text().null())
+        .col(ColumnDef::new(Transactions::TxType).tiny_unsigned().null())
+        .col(
+            ColumnDef::new(Transactions::MaxFeePerGas)
+                .string_len(U256_DIGITS)
+                .null(),
+        )
+        .col(
+            ColumnDef::new(Transactions::MaxPriorityFeePerGas)
+                .string_len(U256_DIGITS)
+                .null(),
+        )
+        .col(
+            ColumnDef::new(Transactions::EffectiveGasPrice)
+                .string_len(U256_DIGITS)
+                .null(),
+        )
+        .col(
+            ColumnDef::new(Transactions::BaseFeePerGas)
+                .string_len(U256_DIGITS)
+                .null(),
+        )
+        .col(
+            ColumnDef::new(Transactions::BlobGasUsed)
+                .big_unsigned()
+                .null(),
+        )
+        .col(
+            ColumnDef::new(Transactions::BlobGasPrice)
+                .string_len(U256_DIGITS)
+                .null(),
+        )
+        .primary_key(&mut key(chain_id, [Transactions::TxId.into_iden()]))
+        .to_owned()
+}
+
+fn internal_transactions(chain_id: bool) -> TableCreateStatement {
+    table(chain_id)
+        .col(
+            ColumnDef::new(InternalTransactions::ParentTxId)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(InternalTransactions::TraceId)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(InternalTransactions::BlockNumber)
+                .big_unsigned()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(InternalTransactions::DateTime)
+                .date_time()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(InternalTransactions::AddressFrom)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(InternalTransactions::AddressTo)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(InternalTransactions::Value)
+                .string_len(U256_DIGITS)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(InternalTransactions::TxType)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(InternalTransactions::IsError)
+                .boolean()
+                .not_null(),
+        )
+        .primary_key(&mut key(
+            chain_id,
+            [
+                InternalTransactions::ParentTxId.into_iden(),
+                InternalTransactions::TraceId.into_iden(),
+            ],
+        ))
+        .to_owned()
+}
+
+fn tokens(chain_id: bool) -> TableCreateStatement {
+    table(chain_id)
+        .col(ColumnDef::new(Tokens::ContractAddress).string().not_null())
+        .col(ColumnDef::new(Tokens::Name).string().not_null())
+        .col(ColumnDef::new(Tokens::Symbol).string().not_null())
+        .col(ColumnDef::new(Tokens::Decimals).unsigned().not_null())
+        .primary_key(&mut key(chain_id, [Tokens::ContractAddress.into_iden()]))
+        .to_owned()
+}
+
+fn token_transfers(chain_id: bool) -> TableCreateStatement {
+    table(chain_id)
+        .col(ColumnDef::new(TokenTransfers::TxId).string().not_null())
+        .col(
+            ColumnDef::new(TokenTransfers::LogIndex)
+                .big_unsigned()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(TokenTransfers::BlockNumber)
+                .big_unsigned()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(TokenTransfers::DateTime)
+                .date_time()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(TokenTransfers::ContractAddress)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(TokenTransfers::AddressFrom)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(TokenTransfers::AddressTo)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(TokenTransfers::Amount)
+                .string_len(U256_DIGITS)
+                .not_null(),
+        )
+        .primary_key(&mut key(
+            chain_id,
+            [
+                TokenTransfers::TxId.into_iden(),
+                TokenTransfers::LogIndex.into_iden(),
+            ],
+        ))
+        .to_owned()
+}
+
+fn nft_transfers(chain_id: bool) -> TableCreateStatement {
+    table(chain_id)
+        .col(ColumnDef::new(NftTransfers::TxId).string().not_null())
+        .col(
+            ColumnDef::new(NftTransfers::LogIndex)
+                .big_unsigned()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NftTransfers::TokenId)
+                .string_len(U256_DIGITS)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NftTransfers::Standard)
+                .string_len(8)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NftTransfers::BlockNumber)
+                .big_unsigned()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NftTransfers::DateTime)
+                .date_time()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NftTransfers::ContractAddress)
+                .string()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NftTransfers::AddressFrom)
+                .string()
+                .not_null(),
+        )
+        .col(ColumnDef::new(NftTransfers::AddressTo).string().not_null())
+        .col(
+            ColumnDef::new(NftTransfers::Quantity)
+                .string_len(U256_DIGITS)
+                .not_null(),
+        )
+        .primary_key(&mut key(
+            chain_id,
+            [
+                NftTransfers::TxId.into_iden(),
+                NftTransfers::LogIndex.into_iden(),
+                NftTransfers::TokenId.into_iden(),
+            ],
+        ))
+        .to_owned()
+}
+
+fn crawl_coverage(chain_id: bool) -> TableCreateStatement {
+    table(chain_id)
+        .col(ColumnDef::new(CrawlCoverage::Address).string().not_null())
+        .col(
+            ColumnDef::new(CrawlCoverage::Source)
+                .string_len(32)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(CrawlCoverage::StartBlock)
+                .big_unsigned()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(CrawlCoverage::EndBlock)
+                .big_unsigned()
+                .not_null(),
+        )
+        .primary_key(&mut key(
+            chain_id,
+            [
+                CrawlCoverage::Address.into_iden(),
+                CrawlCoverage::Source.into_iden(),
+                CrawlCoverage::StartBlock.into_iden(),
+            ],
+        ))
+        .to_owned()
+}
+
+fn watched_addresses(chain_id: bool) -> TableCreateStatement {
+    let mut address = ColumnDef::new(WatchedAddresses::Address);
+    address.string().not_null();
+
+    let mut table = table(chain_id);
+    table
+        .col(
+            ColumnDef::new(WatchedAddresses::Id)
+                .integer()
+                .not_null()
+                .auto_increment()
+                .primary_key(),
+        )
+        .col(if chain_id {
+            &mut address
+        } else {
+            address.unique_key()
+        })
+        .col(
+            ColumnDef::new(WatchedAddresses::StartingBlockNumber)
+                .big_unsigned()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(WatchedAddresses::Status)
+                .string_len(16)
+                .not_null(),
+        )
+        .col(ColumnDef::new(WatchedAddresses::LastCrawlJobId).integer())
+        .col(ColumnDef::new(WatchedAddresses::LastCrawledAt).date_time())
+        .col(
+            ColumnDef::new(WatchedAddresses::CreatedAt)
+                .date_time()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(WatchedAddresses::UpdatedAt)
+                .date_time()
+                .not_null(),
+        );
+
+    // Address is watched once per chain
+    if chain_id {
+        table.index(
+            Index::create()
+                .name("uniq_watched_addresses_chain_id_address")
+                .unique()
+                .col(Chain::ChainId)
+                .col(WatchedAddresses::Address),
+        );
+    }
+
+    table
+}
+
+#[derive(DeriveIden)]
+enum Chain {
+    ChainId,
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    TxId,
+    BlockNumber,
+    BlockHash,
+    DateTime,
+    AddressFrom,
+    AddressTo,
+    Value,
+    TxFee,
+    ContractAddress,
+    IsError,
+    ReceiptStatus,
+    Nonce,
+    TransactionIndex,
+    Gas,
+    GasUsed,
+    GasPrice,
+    CumulativeGasUsed,
+    Input,
+    MethodId,
+    FunctionName,
+    TxType,
+    MaxFeePerGas,
+    MaxPriorityFeePerGas,
+    EffectiveGasPrice,
+    BaseFeePerGas,
+    BlobGasUsed,
+    BlobGasPrice,
+}
+
+#[derive(DeriveIden)]
+enum InternalTransactions {
+    Table,
+    ParentTxId,
+    TraceId,
+    BlockNumber,
+    DateTime,
+    AddressFrom,
+    AddressTo,
+    Value,
+    TxType,
+    IsError,
+}
+
+#[derive(DeriveIden)]
+enum Tokens {
+    Table,
+    ContractAddress,
+    Name,
+    Symbol,
+    Decimals,
+}
+
+#[derive(DeriveIden)]
+enum TokenTransfers {
+    Table,
+    TxId,
+    LogIndex,
+    BlockNumber,
+    DateTime,
+    ContractAddress,
+    AddressFrom,
+    AddressTo,
+    Amount,
+}
+
+#[derive(DeriveIden)]
+enum NftTransfers {
+    Table,
+    TxId,
+    LogIndex,
+    TokenId,
+    Standard,
+    BlockNumber,
+    DateTime,
+    ContractAddress,
+    AddressFrom,
+    AddressTo,
+    Quantity,
+}
+
+#[derive(DeriveIden)]
+enum CrawlCoverage {
+    Table,
+    Address,
+    Source,
+    StartBlock,
+    EndBlock,
+}
+
+#[derive(DeriveIden)]
+enum CrawlJobs {
+    Table,
+}
+
+#[derive(DeriveIden)]
+enum WatchedAddresses {
+    Table,
+    Id,
+    Address,
+    StartingBlockNumber,
+    Status,
+    LastCrawlJobId,
+    LastCrawledAt,
+    CreatedAt,
+    UpdatedAt,
+}
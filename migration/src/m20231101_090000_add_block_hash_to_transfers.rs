@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Nullable, rows crawled so far don't have block hash
+        for table in [
+            InternalTransactions::Table.into_iden(),
+            TokenTransfers::Table.into_iden(),
+            NftTransfers::Table.into_iden(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(ColumnDef::new(Block::BlockHash).string().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [
+            InternalTransactions::Table.into_iden(),
+            TokenTransfers::Table.into_iden(),
+            NftTransfers::Table.into_iden(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Block::BlockHash)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Block {
+    BlockHash,
+}
+
+#[derive(DeriveIden)]
+enum InternalTransactions {
+    Table,
+}
+
+#[derive(DeriveIden)]
+enum TokenTransfers {
+    Table,
+}
+
+#[derive(DeriveIden)]
+enum NftTransfers {
+    Table,
+}
@@ -0,0 +1,98 @@
+use crate::helpers::U256_DIGITS;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Zero amounts were saved unpadded, so they didn't match padded zero in comparisons
+        let padded_zero = "0".repeat(U256_DIGITS as usize);
+
+        for (table, columns) in [
+            (
+                Transactions::Table.into_iden(),
+                vec![
+                    Transactions::Value,
+                    Transactions::TxFee,
+                    Transactions::GasPrice,
+                    Transactions::MaxFeePerGas,
+                    Transactions::MaxPriorityFeePerGas,
+                    Transactions::EffectiveGasPrice,
+                    Transactions::BaseFeePerGas,
+                    Transactions::BlobGasPrice,
+                ]
+                .into_iter()
+                .map(IntoIden::into_iden)
+                .collect::<Vec<_>>(),
+            ),
+            (
+                InternalTransactions::Table.into_iden(),
+                vec![InternalTransactions::Value.into_iden()],
+            ),
+            (
+                TokenTransfers::Table.into_iden(),
+                vec![TokenTransfers::Amount.into_iden()],
+            ),
+            (
+                NftTransfers::Table.into_iden(),
+                vec![
+                    NftTransfers::TokenId.into_iden(),
+                    NftTransfers::Quantity.into_iden(),
+                ],
+            ),
+        ] {
+            for column in columns {
+                manager
+                    .exec_stmt(
+                        Query::update()
+                            .table(table.clone())
+                            .value(column.clone(), padded_zero.clone())
+                            .and_where(Expr::col(column).eq("0"))
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // Padded zero is what every version reads and writes
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    Value,
+    TxFee,
+    GasPrice,
+    MaxFeePerGas,
+    MaxPriorityFeePerGas,
+    EffectiveGasPrice,
+    BaseFeePerGas,
+    BlobGasPrice,
+}
+
+#[derive(DeriveIden)]
+enum InternalTransactions {
+    Table,
+    Value,
+}
+
+#[derive(DeriveIden)]
+enum TokenTransfers {
+    Table,
+    Amount,
+}
+
+#[derive(DeriveIden)]
+enum NftTransfers {
+    Table,
+    TokenId,
+    Quantity,
+}
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BlockHashes::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(BlockHashes::ChainId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(BlockHashes::BlockNumber)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(BlockHashes::BlockHash).string().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(BlockHashes::ChainId)
+                            .col(BlockHashes::BlockNumber),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BlockHashes::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BlockHashes {
+    Table,
+    ChainId,
+    BlockNumber,
+    BlockHash,
+}
@@ -0,0 +1,147 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (name, table, columns) in indexes() {
+            let mut index = Index::create();
+            index.name(name).table(table).col(Chain::ChainId);
+            for column in columns {
+                index.col(column);
+            }
+
+            manager.create_index(index).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (name, table, _) in indexes() {
+            manager
+                .drop_index(Index::drop().name(name).table(table).to_owned())
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Name, table and columns after chain id of every index. Rows of address are looked up
+/// by each of its address columns separately and are ordered by position within chain.
+fn indexes() -> Vec<(&'static str, DynIden, Vec<DynIden>)> {
+    let mut indexes: Vec<(&'static str, DynIden, Vec<DynIden>)> = vec![];
+
+    for (name, address) in [
+        ("idx-transactions-address_from", Participant::AddressFrom),
+        ("idx-transactions-address_to", Participant::AddressTo),
+        (
+            "idx-transactions-contract_address",
+            Participant::ContractAddress,
+        ),
+    ] {
+        indexes.push((
+            name,
+            Transactions::Table.into_iden(),
+            vec![
+                address.into_iden(),
+                Position::BlockNumber.into_iden(),
+                Transactions::TransactionIndex.into_iden(),
+            ],
+        ));
+    }
+
+    for (name, address) in [
+        (
+            "idx-internal_transactions-address_from",
+            Participant::AddressFrom,
+        ),
+        (
+            "idx-internal_transactions-address_to",
+            Participant::AddressTo,
+        ),
+    ] {
+        indexes.push((
+            name,
+            InternalTransactions::Table.into_iden(),
+            vec![address.into_iden(), Position::BlockNumber.into_iden()],
+        ));
+    }
+
+    for (name, table, address) in [
+        (
+            "idx-token_transfers-address_from",
+            TokenTransfers::Table.into_iden(),
+            Participant::AddressFrom,
+        ),
+        (
+            "idx-token_transfers-address_to",
+            TokenTransfers::Table.into_iden(),
+            Participant::AddressTo,
+        ),
+        (
+            "idx-nft_transfers-address_from",
+            NftTransfers::Table.into_iden(),
+            Participant::AddressFrom,
+        ),
+        (
+            "idx-nft_transfers-address_to",
+            NftTransfers::Table.into_iden(),
+            Participant::AddressTo,
+        ),
+    ] {
+        indexes.push((
+            name,
+            table,
+            vec![
+                address.into_iden(),
+                Position::BlockNumber.into_iden(),
+                Position::LogIndex.into_iden(),
+            ],
+        ));
+    }
+
+    indexes
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    TransactionIndex,
+}
+
+#[derive(DeriveIden)]
+enum InternalTransactions {
+    Table,
+}
+
+#[derive(DeriveIden)]
+enum TokenTransfers {
+    Table,
+}
+
+#[derive(DeriveIden)]
+enum NftTransfers {
+    Table,
+}
+
+#[derive(DeriveIden)]
+enum Chain {
+    ChainId,
+}
+
+#[derive(DeriveIden)]
+enum Participant {
+    AddressFrom,
+    AddressTo,
+    ContractAddress,
+}
+
+#[derive(DeriveIden)]
+enum Position {
+    BlockNumber,
+    LogIndex,
+}
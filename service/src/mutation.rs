@@ -1,5 +1,22 @@
-use ::entity::{transactions, transactions::Entity as Post};
-use sea_orm::*;
+use crate::merge_block_ranges;
+use ::entity::{
+    block_hashes, block_hashes::Entity as BlockHashes, crawl_coverage,
+    crawl_coverage::CoverageSource, crawl_coverage::Entity as CrawlCoverage, crawl_jobs,
+    crawl_jobs::CrawlJobStatus, crawl_jobs::Entity as CrawlJobs, internal_transactions,
+    internal_transactions::Entity as InternalTransactions, nft_transfers,
+    nft_transfers::Entity as NftTransfers, token_transfers,
+    token_transfers::Entity as TokenTransfers, tokens, tokens::Entity as Tokens, transactions,
+    transactions::Entity as Post, unsigned::U64, watched_addresses,
+    watched_addresses::Entity as WatchedAddresses, watched_addresses::WatchStatus,
+};
+use chrono::Utc;
+use sea_orm::{sea_query::Expr, *};
+
+// Largest number, which every supported database can bind
+const MAX_BOUND_NUMBER: u64 = i64::MAX as u64;
+
+// Number of block hashes, which are recorded by one statement
+const BLOCK_HASHES_PER_INSERT: usize = 1000;
 
 pub struct Mutation;
 
@@ -11,17 +28,561 @@ impl Mutation {
         let unfetched_transactions_models: Vec<_> = unfetched_transactions
             .into_iter()
             .map(|unfetched_transaction| transactions::ActiveModel {
+                chain_id: Set(unfetched_transaction.chain_id),
                 tx_id: Set(unfetched_transaction.tx_id.to_owned()),
+                block_hash: Set(unfetched_transaction.block_hash.to_owned()),
                 address_from: Set(unfetched_transaction.address_from.to_owned()),
                 address_to: Set(unfetched_transaction.address_to.to_owned()),
                 value: Set(unfetched_transaction.value.to_owned()),
                 tx_fee: Set(unfetched_transaction.tx_fee.to_owned()),
+                contract_address: Set(unfetched_transaction.contract_address.to_owned()),
+                is_error: Set(unfetched_transaction.is_error),
+                receipt_status: Set(unfetched_transaction.receipt_status),
+                nonce: Set(unfetched_transaction.nonce),
+                transaction_index: Set(unfetched_transaction.transaction_index),
+                gas: Set(unfetched_transaction.gas),
+                gas_used: Set(unfetched_transaction.gas_used),
+                gas_price: Set(unfetched_transaction.gas_price),
+                cumulative_gas_used: Set(unfetched_transaction.cumulative_gas_used),
+                input: Set(unfetched_transaction.input.to_owned()),
+                method_id: Set(unfetched_transaction.method_id.to_owned()),
+                function_name: Set(unfetched_transaction.function_name.to_owned()),
+                tx_type: Set(unfetched_transaction.tx_type),
+                max_fee_per_gas: Set(unfetched_transaction.max_fee_per_gas),
+                max_priority_fee_per_gas: Set(unfetched_transaction.max_priority_fee_per_gas),
+                effective_gas_price: Set(unfetched_transaction.effective_gas_price),
+                base_fee_per_gas: Set(unfetched_transaction.base_fee_per_gas),
+                blob_gas_used: Set(unfetched_transaction.blob_gas_used),
+                blob_gas_price: Set(unfetched_transaction.blob_gas_price),
                 block_number: Set(unfetched_transaction.block_number),
                 date_time: Set(unfetched_transaction.date_time),
             })
             .collect();
 
+        // Transaction between two crawled addresses may be stored already
         Post::insert_many(unfetched_transactions_models)
+            .on_conflict(
+                sea_query::OnConflict::columns([
+                    transactions::Column::ChainId,
+                    transactions::Column::TxId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Stores fee breakdown of already saved transactions, along with fee including blob fee
+    pub async fn update_fee_details(
+        db: &DbConn,
+        transactions: Vec<transactions::Model>,
+    ) -> Result<(), DbErr> {
+        let txn = db.begin().await?;
+
+        for transaction in transactions {
+            Post::update(transactions::ActiveModel {
+                chain_id: Unchanged(transaction.chain_id),
+                tx_id: Unchanged(transaction.tx_id),
+                tx_fee: Set(transaction.tx_fee),
+                tx_type: Set(transaction.tx_type),
+                max_fee_per_gas: Set(transaction.max_fee_per_gas),
+                max_priority_fee_per_gas: Set(transaction.max_priority_fee_per_gas),
+                effective_gas_price: Set(transaction.effective_gas_price),
+                base_fee_per_gas: Set(transaction.base_fee_per_gas),
+                blob_gas_used: Set(transaction.blob_gas_used),
+                blob_gas_price: Set(transaction.blob_gas_price),
+                ..Default::default()
+            })
+            .exec(&txn)
+            .await?;
+        }
+
+        txn.commit().await
+    }
+
+    pub async fn save_internal_transactions(
+        db: &DbConn,
+        unfetched_internal_transactions: Vec<internal_transactions::Model>,
+    ) -> Result<(), DbErr> {
+        let unfetched_internal_transactions_models: Vec<_> = unfetched_internal_transactions
+            .into_iter()
+            .map(
+                |unfetched_internal_transaction| internal_transactions::ActiveModel {
+                    chain_id: Set(unfetched_internal_transaction.chain_id),
+                    parent_tx_id: Set(unfetched_internal_transaction.parent_tx_id),
+                    trace_id: Set(unfetched_internal_transaction.trace_id),
+                    block_number: Set(unfetched_internal_transaction.block_number),
+                    block_hash: Set(unfetched_internal_transaction.block_hash),
+                    date_time: Set(unfetched_internal_transaction.date_time),
+                    address_from: Set(unfetched_internal_transaction.address_from),
+                    address_to: Set(unfetched_internal_transaction.address_to),
+                    value: Set(unfetched_internal_transaction.value),
+                    tx_type: Set(unfetched_internal_transaction.tx_type),
+                    is_error: Set(unfetched_internal_transaction.is_error),
+                },
+            )
+            .collect();
+
+        InternalTransactions::insert_many(unfetched_internal_transactions_models)
+            .on_conflict(
+                sea_query::OnConflict::columns([
+                    internal_transactions::Column::ChainId,
+                    internal_transactions::Column::ParentTxId,
+                    internal_transactions::Column::TraceId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn save_token_transfers(
+        db: &DbConn,
+        unfetched_token_transfers: Vec<token_transfers::Model>,
+    ) -> Result<(), DbErr> {
+        let unfetched_token_transfers_models: Vec<_> = unfetched_token_transfers
+            .into_iter()
+            .map(|unfetched_token_transfer| token_transfers::ActiveModel {
+                chain_id: Set(unfetched_token_transfer.chain_id),
+                tx_id: Set(unfetched_token_transfer.tx_id),
+                log_index: Set(unfetched_token_transfer.log_index),
+                block_number: Set(unfetched_token_transfer.block_number),
+                block_hash: Set(unfetched_token_transfer.block_hash),
+                date_time: Set(unfetched_token_transfer.date_time),
+                contract_address: Set(unfetched_token_transfer.contract_address),
+                address_from: Set(unfetched_token_transfer.address_from),
+                address_to: Set(unfetched_token_transfer.address_to),
+                amount: Set(unfetched_token_transfer.amount),
+            })
+            .collect();
+
+        TokenTransfers::insert_many(unfetched_token_transfers_models)
+            .on_conflict(
+                sea_query::OnConflict::columns([
+                    token_transfers::Column::ChainId,
+                    token_transfers::Column::TxId,
+                    token_transfers::Column::LogIndex,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Saves metadata of tokens, which are not known yet
+    pub async fn save_tokens(db: &DbConn, tokens: Vec<tokens::Model>) -> Result<(), DbErr> {
+        let tokens_models: Vec<_> = tokens
+            .into_iter()
+            .map(|token| tokens::ActiveModel {
+                chain_id: Set(token.chain_id),
+                contract_address: Set(token.contract_address),
+                name: Set(token.name),
+                symbol: Set(token.symbol),
+                decimals: Set(token.decimals),
+            })
+            .collect();
+
+        Tokens::insert_many(tokens_models)
+            .on_conflict(
+                sea_query::OnConflict::columns([
+                    tokens::Column::ChainId,
+                    tokens::Column::ContractAddress,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn save_nft_transfers(
+        db: &DbConn,
+        unfetched_nft_transfers: Vec<nft_transfers::Model>,
+    ) -> Result<(), DbErr> {
+        let unfetched_nft_transfers_models: Vec<_> = unfetched_nft_transfers
+            .into_iter()
+            .map(|unfetched_nft_transfer| nft_transfers::ActiveModel {
+                chain_id: Set(unfetched_nft_transfer.chain_id),
+                tx_id: Set(unfetched_nft_transfer.tx_id),
+                log_index: Set(unfetched_nft_transfer.log_index),
+                token_id: Set(unfetched_nft_transfer.token_id),
+                standard: Set(unfetched_nft_transfer.standard),
+                block_number: Set(unfetched_nft_transfer.block_number),
+                block_hash: Set(unfetched_nft_transfer.block_hash),
+                date_time: Set(unfetched_nft_transfer.date_time),
+                contract_address: Set(unfetched_nft_transfer.contract_address),
+                address_from: Set(unfetched_nft_transfer.address_from),
+                address_to: Set(unfetched_nft_transfer.address_to),
+                quantity: Set(unfetched_nft_transfer.quantity),
+            })
+            .collect();
+
+        NftTransfers::insert_many(unfetched_nft_transfers_models)
+            .on_conflict(
+                sea_query::OnConflict::columns([
+                    nft_transfers::Column::ChainId,
+                    nft_transfers::Column::TxId,
+                    nft_transfers::Column::LogIndex,
+                    nft_transfers::Column::TokenId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Creates pending crawl job for given address on given chain since starting block
+    pub async fn create_crawl_job(
+        db: &DbConn,
+        chain_id: u64,
+        address: String,
+        starting_block_number: u64,
+        ending_block_number: Option<u64>,
+    ) -> Result<crawl_jobs::Model, DbErr> {
+        let now = Utc::now().naive_utc();
+
+        crawl_jobs::ActiveModel {
+            chain_id: Set(chain_id.into()),
+            address: Set(address),
+            starting_block_number: Set(starting_block_number.into()),
+            ending_block_number: Set(ending_block_number.map(U64)),
+            status: Set(CrawlJobStatus::Pending),
+            ranges_total: Set(U64(0)),
+            ranges_done: Set(U64(0)),
+            pages_fetched: Set(U64(0)),
+            rows_saved: Set(U64(0)),
+            error: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+    }
+
+    /// Updates status of crawl job, error is recorded for failed jobs
+    pub async fn update_crawl_job_status(
+        db: &DbConn,
+        id: i32,
+        status: CrawlJobStatus,
+        error: Option<String>,
+    ) -> Result<(), DbErr> {
+        CrawlJobs::update(crawl_jobs::ActiveModel {
+            id: Unchanged(id),
+            status: Set(status),
+            error: Set(error),
+            updated_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+        .exec(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_crawl_job_progress(
+        db: &DbConn,
+        id: i32,
+        ranges_total: u64,
+        ranges_done: u64,
+        pages_fetched: u64,
+        rows_saved: u64,
+    ) -> Result<(), DbErr> {
+        CrawlJobs::update(crawl_jobs::ActiveModel {
+            id: Unchanged(id),
+            ranges_total: Set(ranges_total.into()),
+            ranges_done: Set(ranges_done.into()),
+            pages_fetched: Set(pages_fetched.into()),
+            rows_saved: Set(rows_saved.into()),
+            updated_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+        .exec(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records block range as fully fetched for address on given chain from given source.
+    /// Range is merged with overlapping and adjacent intervals, so they never overlap.
+    pub async fn add_crawl_coverage(
+        db: &DbConn,
+        chain_id: u64,
+        address: &str,
+        source: CoverageSource,
+        (r_start, r_end): (u64, u64),
+    ) -> Result<(), DbErr> {
+        let txn = db.begin().await?;
+
+        // Databases bind numbers as signed 64-bit, so block after the last storable one can't be bound
+        let touching_block_ranges = CrawlCoverage::find()
+            .filter(crawl_coverage::Column::ChainId.eq(chain_id))
+            .filter(crawl_coverage::Column::Address.eq(address))
+            .filter(crawl_coverage::Column::Source.eq(source))
+            .filter(
+                crawl_coverage::Column::StartBlock
+                    .lte(r_end.saturating_add(1).min(MAX_BOUND_NUMBER)),
+            )
+            .filter(crawl_coverage::Column::EndBlock.gte(r_start.saturating_sub(1)))
+            .all(&txn)
+            .await?;
+
+        let mut block_ranges: Vec<_> = touching_block_ranges
+            .iter()
+            .map(|touching| (touching.start_block.0, touching.end_block.0))
+            .collect();
+        block_ranges.push((r_start, r_end));
+
+        if !touching_block_ranges.is_empty() {
+            CrawlCoverage::delete_many()
+                .filter(crawl_coverage::Column::ChainId.eq(chain_id))
+                .filter(crawl_coverage::Column::Address.eq(address))
+                .filter(crawl_coverage::Column::Source.eq(source))
+                .filter(
+                    crawl_coverage::Column::StartBlock.is_in(
+                        touching_block_ranges
+                            .iter()
+                            .map(|touching| touching.start_block),
+                    ),
+                )
+                .exec(&txn)
+                .await?;
+        }
+
+        let merged_models: Vec<_> = merge_block_ranges(block_ranges)
+            .into_iter()
+            .map(|(start_block, end_block)| crawl_coverage::ActiveModel {
+                chain_id: Set(chain_id.into()),
+                address: Set(address.to_owned()),
+                source: Set(source),
+                start_block: Set(start_block.into()),
+                end_block: Set(end_block.into()),
+            })
+            .collect();
+
+        CrawlCoverage::insert_many(merged_models)
+            .exec_without_returning(&txn)
+            .await?;
+
+        txn.commit().await
+    }
+
+    pub async fn create_watched_address(
+        db: &DbConn,
+        chain_id: u64,
+        address: String,
+        starting_block_number: u64,
+    ) -> Result<watched_addresses::Model, DbErr> {
+        let now = Utc::now().naive_utc();
+
+        watched_addresses::ActiveModel {
+            chain_id: Set(chain_id.into()),
+            address: Set(address),
+            starting_block_number: Set(starting_block_number.into()),
+            status: Set(WatchStatus::Active),
+            last_crawl_job_id: Set(None),
+            last_crawled_at: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+    }
+
+    /// Pauses or resumes watched address, returns false if there is no such watch
+    pub async fn update_watched_address_status(
+        db: &DbConn,
+        id: i32,
+        status: WatchStatus,
+    ) -> Result<bool, DbErr> {
+        let result = WatchedAddresses::update_many()
+            .col_expr(watched_addresses::Column::Status, Expr::value(status))
+            .col_expr(
+                watched_addresses::Column::UpdatedAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .filter(watched_addresses::Column::Id.eq(id))
+            .exec(db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Records crawl job, started by scheduler for watched address
+    pub async fn record_watched_address_crawl(
+        db: &DbConn,
+        id: i32,
+        crawl_job_id: i32,
+    ) -> Result<(), DbErr> {
+        let now = Utc::now().naive_utc();
+
+        WatchedAddresses::update(watched_addresses::ActiveModel {
+            id: Unchanged(id),
+            last_crawl_job_id: Set(Some(crawl_job_id)),
+            last_crawled_at: Set(Some(now)),
+            updated_at: Set(now),
+            ..Default::default()
+        })
+        .exec(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes crawl job, if it has completed. Returns false if there is no such completed job.
+    pub async fn delete_completed_crawl_job(db: &DbConn, id: i32) -> Result<bool, DbErr> {
+        let result = CrawlJobs::delete_many()
+            .filter(crawl_jobs::Column::Id.eq(id))
+            .filter(crawl_jobs::Column::Status.eq(CrawlJobStatus::Completed))
+            .exec(db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Stops watching address, crawled data is kept. Returns false if there is no such watch.
+    pub async fn delete_watched_address(db: &DbConn, id: i32) -> Result<bool, DbErr> {
+        let result = WatchedAddresses::delete_by_id(id).exec(db).await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Deletes rows of given chain since first orphaned block, along with recorded block hashes,
+    /// and removes those blocks from coverage. Blocks on top of orphaned block were reorged too.
+    /// Returns addresses, whose coverage included removed blocks.
+    pub async fn remove_orphaned_blocks(
+        db: &DbConn,
+        chain_id: u64,
+        first_orphaned_block: u64,
+    ) -> Result<Vec<String>, DbErr> {
+        let first_orphaned_block = first_orphaned_block.min(MAX_BOUND_NUMBER);
+        let txn = db.begin().await?;
+
+        Post::delete_many()
+            .filter(transactions::Column::ChainId.eq(chain_id))
+            .filter(transactions::Column::BlockNumber.gte(first_orphaned_block))
+            .exec(&txn)
+            .await?;
+        InternalTransactions::delete_many()
+            .filter(internal_transactions::Column::ChainId.eq(chain_id))
+            .filter(internal_transactions::Column::BlockNumber.gte(first_orphaned_block))
+            .exec(&txn)
+            .await?;
+        TokenTransfers::delete_many()
+            .filter(token_transfers::Column::ChainId.eq(chain_id))
+            .filter(token_transfers::Column::BlockNumber.gte(first_orphaned_block))
+            .exec(&txn)
+            .await?;
+        NftTransfers::delete_many()
+            .filter(nft_transfers::Column::ChainId.eq(chain_id))
+            .filter(nft_transfers::Column::BlockNumber.gte(first_orphaned_block))
+            .exec(&txn)
+            .await?;
+        BlockHashes::delete_many()
+            .filter(block_hashes::Column::ChainId.eq(chain_id))
+            .filter(block_hashes::Column::BlockNumber.gte(first_orphaned_block))
+            .exec(&txn)
+            .await?;
+
+        let orphaned_block_ranges = CrawlCoverage::find()
+            .filter(crawl_coverage::Column::ChainId.eq(chain_id))
+            .filter(crawl_coverage::Column::EndBlock.gte(first_orphaned_block))
+            .all(&txn)
+            .await?;
+
+        let mut addresses = vec![];
+        let mut remaining_models = vec![];
+
+        for orphaned in orphaned_block_ranges {
+            CrawlCoverage::delete_by_id((
+                orphaned.chain_id,
+                orphaned.address.clone(),
+                orphaned.source,
+                orphaned.start_block,
+            ))
+            .exec(&txn)
+            .await?;
+
+            // Part of interval before orphaned block stays covered
+            if orphaned.start_block.0 < first_orphaned_block {
+                remaining_models.push(crawl_coverage::ActiveModel {
+                    chain_id: Set(orphaned.chain_id),
+                    address: Set(orphaned.address.clone()),
+                    source: Set(orphaned.source),
+                    start_block: Set(orphaned.start_block),
+                    end_block: Set(U64(first_orphaned_block - 1)),
+                });
+            }
+
+            if !addresses.contains(&orphaned.address) {
+                addresses.push(orphaned.address);
+            }
+        }
+
+        if !remaining_models.is_empty() {
+            CrawlCoverage::insert_many(remaining_models)
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(addresses)
+    }
+
+    /// Records hashes of blocks of given chain, replacing previously recorded ones
+    pub async fn save_block_hashes(
+        db: &DbConn,
+        chain_id: u64,
+        block_hashes: Vec<(u64, String)>,
+    ) -> Result<(), DbErr> {
+        for chunk in block_hashes.chunks(BLOCK_HASHES_PER_INSERT) {
+            BlockHashes::insert_many(chunk.iter().map(|(block_number, block_hash)| {
+                block_hashes::ActiveModel {
+                    chain_id: Set(U64(chain_id)),
+                    block_number: Set(U64(*block_number)),
+                    block_hash: Set(block_hash.to_lowercase()),
+                }
+            }))
+            .on_conflict(
+                sea_query::OnConflict::columns([
+                    block_hashes::Column::ChainId,
+                    block_hashes::Column::BlockNumber,
+                ])
+                .update_column(block_hashes::Column::BlockHash)
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes recorded hashes of blocks of given chain, which are no longer re-checked for reorgs
+    pub async fn remove_block_hashes_before(
+        db: &DbConn,
+        chain_id: u64,
+        block_number: u64,
+    ) -> Result<(), DbErr> {
+        BlockHashes::delete_many()
+            .filter(block_hashes::Column::ChainId.eq(chain_id))
+            .filter(block_hashes::Column::BlockNumber.lt(block_number.min(MAX_BOUND_NUMBER)))
             .exec(db)
             .await?;
 
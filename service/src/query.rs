@@ -1,74 +1,1476 @@
-use ::entity::{transactions, transactions::Entity as Transactions};
-use sea_orm::*;
-use std::collections::BTreeSet;
+use ::entity::{
+    block_hashes, block_hashes::Entity as BlockHashes, crawl_coverage,
+    crawl_coverage::CoverageSource, crawl_coverage::Entity as CrawlCoverage, crawl_jobs,
+    crawl_jobs::CrawlJobStatus, crawl_jobs::Entity as CrawlJobs, internal_transactions,
+    internal_transactions::Entity as InternalTransactions, nft_transfers,
+    nft_transfers::Entity as NftTransfers, token_transfers,
+    token_transfers::Entity as TokenTransfers, tokens, tokens::Entity as Tokens, transactions,
+    transactions::Entity as Transactions, u256::U256, unsigned::U64, watched_addresses,
+    watched_addresses::Entity as WatchedAddresses, watched_addresses::WatchStatus,
+};
+use sea_orm::{
+    prelude::{ChronoDate, ChronoDateTime},
+    sea_query::{Expr, Func},
+    *,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+// Number of transactions loaded at once, while building address activity
+const ACTIVITY_CHUNK_SIZE: u64 = 1000;
 
 pub struct Query;
 
-#[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
-enum QueryAs {
-    BlockNumber,
+/// Point in chain history: end of given block or end of given day (UTC)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockOrDate {
+    Block(u64),
+    Date(ChronoDate),
+}
+
+/// Balance, rebuilt by replaying crawled history back from known anchor balance
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BalanceAt {
+    pub balance: U256,
+    pub anchor_balance: U256,
+    pub anchor_block: u64,
+    /// Amounts received, sent and paid as fees after requested point, up to anchor block
+    pub received_since: U256,
+    pub sent_since: U256,
+    pub fees_since: U256,
+    /// Part of fees, which was burnt, and part paid as priority tips.
+    /// Fees of transactions without fee breakdown are in neither of them.
+    pub burnt_fees_since: U256,
+    pub tips_since: U256,
+    /// False if crawled history doesn't add up to anchor balance, e.g. because of coverage gaps
+    pub consistent: bool,
+}
+
+/// Direction of transaction, relative to selected address.
+/// Transfers to self count as both incoming and outgoing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    In,
+    Out,
+    #[serde(rename = "self")]
+    SelfTransfer,
+}
+
+impl Direction {
+    /// Direction of transaction relative to selected address, transfers to self are neither in nor out
+    fn of(transaction: &transactions::Model, address: &str) -> Option<Self> {
+        let is_sender = transaction.address_from.eq_ignore_ascii_case(address);
+        let is_recipient = transaction
+            .recipient()
+            .is_some_and(|recipient| recipient.eq_ignore_ascii_case(address));
+
+        match (is_sender, is_recipient) {
+            (true, true) => Some(Self::SelfTransfer),
+            (true, false) => Some(Self::Out),
+            (false, true) => Some(Self::In),
+            (false, false) => None,
+        }
+    }
+}
+
+/// Outcome of transaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionStatus {
+    Succeeded,
+    Failed,
+}
+
+/// Filters of selected address transactions, empty filters match everything
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TransactionFilter {
+    pub from_block: Option<u64>,
+    pub to_block: Option<u64>,
+    /// First and last day (UTC) of transactions, both inclusive
+    pub from_date: Option<ChronoDate>,
+    pub to_date: Option<ChronoDate>,
+    pub direction: Option<Direction>,
+    /// Value range in wei, both inclusive
+    pub min_value: Option<U256>,
+    pub max_value: Option<U256>,
+    /// Address on the other side of transaction
+    pub counterparty: Option<String>,
+    /// Minimal transaction fee in wei
+    pub min_fee: Option<U256>,
+    pub status: Option<TransactionStatus>,
+}
+
+/// Column, which transactions are sorted by
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    #[default]
+    Block,
+    Time,
+    Value,
+    Fee,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Asc => Order::Asc,
+            SortOrder::Desc => Order::Desc,
+        }
+    }
+}
+
+/// Sorting of selected address transactions, chain order by default.
+/// Ties are broken by block number and transaction index, so pages are stable.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TransactionSort {
+    #[serde(default)]
+    pub sort: SortKey,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+impl TransactionFilter {
+    fn condition(&self, address: &str) -> Condition {
+        let mut condition = Condition::all().add(match self.direction {
+            Some(Direction::In) => Condition::any()
+                .add(transactions::Column::AddressTo.eq(address))
+                .add(transactions::Column::ContractAddress.eq(address)),
+            Some(Direction::Out) => {
+                Condition::all().add(transactions::Column::AddressFrom.eq(address))
+            }
+            Some(Direction::SelfTransfer) => Condition::all()
+                .add(transactions::Column::AddressFrom.eq(address))
+                .add(transactions::Column::AddressTo.eq(address)),
+            None => Condition::any()
+                .add(transactions::Column::AddressFrom.eq(address))
+                .add(transactions::Column::AddressTo.eq(address))
+                .add(transactions::Column::ContractAddress.eq(address)),
+        });
+
+        if let Some(from_block) = self.from_block {
+            condition = condition.add(transactions::Column::BlockNumber.gte(from_block));
+        }
+        if let Some(to_block) = self.to_block {
+            condition = condition.add(transactions::Column::BlockNumber.lte(to_block));
+        }
+        if let Some(from_date) = self.from_date.and_then(|date| date.and_hms_opt(0, 0, 0)) {
+            condition = condition.add(transactions::Column::DateTime.gte(from_date));
+        }
+        if let Some(to_date) = self.to_date {
+            // Dates past the end of calendar can't exclude anything
+            if let Some(next_day_start) = to_date
+                .succ_opt()
+                .and_then(|next_day| next_day.and_hms_opt(0, 0, 0))
+            {
+                condition = condition.add(transactions::Column::DateTime.lt(next_day_start));
+            }
+        }
+        // Amounts are zero padded, so string comparison matches numeric one
+        if let Some(min_value) = self.min_value {
+            condition = condition.add(transactions::Column::Value.gte(min_value));
+        }
+        if let Some(max_value) = self.max_value {
+            condition = condition.add(transactions::Column::Value.lte(max_value));
+        }
+        if let Some(min_fee) = self.min_fee {
+            condition = condition.add(transactions::Column::TxFee.gte(min_fee));
+        }
+        if let Some(counterparty) = &self.counterparty {
+            condition = condition.add(
+                Condition::any()
+                    .add(
+                        Condition::all()
+                            .add(transactions::Column::AddressFrom.eq(address))
+                            .add(
+                                Condition::any()
+                                    .add(transactions::Column::AddressTo.eq(counterparty))
+                                    .add(transactions::Column::ContractAddress.eq(counterparty)),
+                            ),
+                    )
+                    .add(
+                        Condition::all()
+                            .add(transactions::Column::AddressFrom.eq(counterparty))
+                            .add(
+                                Condition::any()
+                                    .add(transactions::Column::AddressTo.eq(address))
+                                    .add(transactions::Column::ContractAddress.eq(address)),
+                            ),
+                    ),
+            );
+        }
+
+        // Unknown receipt status must not turn the whole condition into NULL
+        let failed = Condition::any()
+            .add(transactions::Column::IsError.eq(true))
+            .add(
+                Condition::all()
+                    .add(transactions::Column::ReceiptStatus.is_not_null())
+                    .add(transactions::Column::ReceiptStatus.eq(false)),
+            );
+
+        match self.status {
+            Some(TransactionStatus::Failed) => condition.add(failed),
+            Some(TransactionStatus::Succeeded) => condition.add(failed.not()),
+            None => condition,
+        }
+    }
+}
+
+/// Transaction along with its fee, split into burnt fee and priority tip
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TransactionWithFees {
+    #[serde(flatten)]
+    pub transaction: transactions::Model,
+    pub burnt_fee: Option<U256>,
+    pub priority_fee: Option<U256>,
+}
+
+/// Position of transaction in export order, next chunk starts after it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionKey {
+    pub block_number: u64,
+    pub transaction_index: u64,
+    pub tx_id: String,
+}
+
+impl From<&transactions::Model> for TransactionKey {
+    fn from(transaction: &transactions::Model) -> Self {
+        Self {
+            block_number: transaction.block_number.0,
+            transaction_index: transaction.transaction_index.map_or(0, |index| index.0),
+            tx_id: transaction.tx_id.clone(),
+        }
+    }
+}
+
+impl From<transactions::Model> for TransactionWithFees {
+    fn from(transaction: transactions::Model) -> Self {
+        Self {
+            burnt_fee: transaction.burnt_fee(),
+            priority_fee: transaction.priority_fee(),
+            transaction,
+        }
+    }
+}
+
+/// Quantity of single NFT, held by address
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct NftHolding {
+    pub contract_address: String,
+    pub token_id: U256,
+    pub standard: nft_transfers::NftStandard,
+    pub quantity: U256,
+}
+
+/// NFTs held by address, rebuilt by replaying crawled transfers
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct NftHoldings {
+    pub holdings: Vec<NftHolding>,
+    /// False if address sent NFTs, which it didn't receive in crawled transfers,
+    /// e.g. because they were received before the earliest crawled block
+    pub consistent: bool,
+}
+
+/// Totals of all crawled transactions of address.
+/// Values of failed transactions are not counted, their fees are.
+/// Transfers to self are counted neither as incoming nor as outgoing,
+/// but their value is both received and sent.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct AddressSummary {
+    pub total_received: U256,
+    pub total_sent: U256,
+    pub total_fees: U256,
+    /// Parts of fees, which were burnt and paid as priority tips.
+    /// Fees of transactions without fee breakdown are in neither of them.
+    pub total_burnt_fees: U256,
+    pub total_tips: U256,
+    pub transactions_count: u64,
+    pub incoming_count: u64,
+    pub outgoing_count: u64,
+    pub self_count: u64,
+    pub failed_count: u64,
+    pub first_activity: Option<ChronoDateTime>,
+    pub first_block: Option<u64>,
+    pub last_activity: Option<ChronoDateTime>,
+    pub last_block: Option<u64>,
+}
+
+impl AddressSummary {
+    /// Adds transaction, transactions are added in chain order
+    fn add(
+        &mut self,
+        transaction: &TransactionWithFees,
+        direction: Option<Direction>,
+    ) -> Result<(), DbErr> {
+        let TransactionWithFees {
+            transaction,
+            burnt_fee,
+            priority_fee,
+        } = transaction;
+        let is_failed = transaction.is_failed();
+
+        self.transactions_count += 1;
+        match direction {
+            Some(Direction::In) => self.incoming_count += 1,
+            Some(Direction::Out) => self.outgoing_count += 1,
+            Some(Direction::SelfTransfer) => self.self_count += 1,
+            None => {}
+        }
+        if is_failed {
+            self.failed_count += 1;
+        }
+
+        if !is_failed {
+            if matches!(direction, Some(Direction::In | Direction::SelfTransfer)) {
+                add_amount(&mut self.total_received, transaction.value)?;
+            }
+            if matches!(direction, Some(Direction::Out | Direction::SelfTransfer)) {
+                add_amount(&mut self.total_sent, transaction.value)?;
+            }
+        }
+        if matches!(direction, Some(Direction::Out | Direction::SelfTransfer)) {
+            add_amount(&mut self.total_fees, transaction.tx_fee)?;
+            if let Some(burnt_fee) = burnt_fee {
+                add_amount(&mut self.total_burnt_fees, *burnt_fee)?;
+            }
+            if let Some(priority_fee) = priority_fee {
+                add_amount(&mut self.total_tips, *priority_fee)?;
+            }
+        }
+
+        if self.first_activity.is_none() {
+            self.first_activity = Some(transaction.date_time);
+            self.first_block = Some(transaction.block_number.0);
+        }
+        self.last_activity = Some(transaction.date_time);
+        self.last_block = Some(transaction.block_number.0);
+        Ok(())
+    }
+}
+
+/// Address, which selected address has transacted with
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Counterparty {
+    pub address: String,
+    pub transactions_count: u64,
+    /// Amounts received from and sent to counterparty
+    pub received: U256,
+    pub sent: U256,
+    pub volume: U256,
+}
+
+impl Counterparty {
+    fn new(address: String) -> Self {
+        Self {
+            address,
+            transactions_count: 0,
+            received: U256::zero(),
+            sent: U256::zero(),
+            volume: U256::zero(),
+        }
+    }
+
+    /// Counterparty address of transaction and whether it was sent to it.
+    /// Transfers to self and contract creations have none.
+    fn of(
+        transaction: &transactions::Model,
+        direction: Option<Direction>,
+    ) -> Option<(String, bool)> {
+        match direction {
+            Some(Direction::In) => Some((transaction.address_from.clone(), false)),
+            Some(Direction::Out) => transaction
+                .recipient()
+                .map(|recipient| (recipient.to_string(), true)),
+            Some(Direction::SelfTransfer) | None => None,
+        }
+    }
+
+    fn add(&mut self, transaction: &transactions::Model, is_sent: bool) -> Result<(), DbErr> {
+        self.transactions_count += 1;
+        if !transaction.is_failed() {
+            if is_sent {
+                add_amount(&mut self.sent, transaction.value)?;
+            } else {
+                add_amount(&mut self.received, transaction.value)?;
+            }
+            add_amount(&mut self.volume, transaction.value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Counterparties with most volume and most transactions
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TopCounterparties {
+    pub by_volume: Vec<Counterparty>,
+    pub by_count: Vec<Counterparty>,
+}
+
+/// Activity of address in single calendar month (UTC).
+/// Transfers to self are counted the same way as in [`AddressSummary`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MonthlyActivity {
+    /// `YYYY-MM`
+    pub month: String,
+    pub transactions_count: u64,
+    pub incoming_count: u64,
+    pub outgoing_count: u64,
+    pub self_count: u64,
+    pub received: U256,
+    pub sent: U256,
+    pub fees: U256,
+}
+
+impl MonthlyActivity {
+    fn new(month: String) -> Self {
+        Self {
+            month,
+            transactions_count: 0,
+            incoming_count: 0,
+            outgoing_count: 0,
+            self_count: 0,
+            received: U256::zero(),
+            sent: U256::zero(),
+            fees: U256::zero(),
+        }
+    }
+
+    fn add(
+        &mut self,
+        transaction: &transactions::Model,
+        direction: Option<Direction>,
+    ) -> Result<(), DbErr> {
+        let is_failed = transaction.is_failed();
+
+        self.transactions_count += 1;
+        match direction {
+            Some(Direction::In) => self.incoming_count += 1,
+            Some(Direction::Out) => self.outgoing_count += 1,
+            Some(Direction::SelfTransfer) => self.self_count += 1,
+            None => {}
+        }
+
+        if !is_failed {
+            if matches!(direction, Some(Direction::In | Direction::SelfTransfer)) {
+                add_amount(&mut self.received, transaction.value)?;
+            }
+            if matches!(direction, Some(Direction::Out | Direction::SelfTransfer)) {
+                add_amount(&mut self.sent, transaction.value)?;
+            }
+        }
+        if matches!(direction, Some(Direction::Out | Direction::SelfTransfer)) {
+            add_amount(&mut self.fees, transaction.tx_fee)?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything shown on address summary page
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct AddressActivity {
+    pub summary: AddressSummary,
+    pub top_counterparties: TopCounterparties,
+    pub monthly_activity: Vec<MonthlyActivity>,
+}
+
+/// Sorts inclusive block ranges and merges overlapping and adjacent ones
+pub fn merge_block_ranges(mut block_ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    block_ranges.sort_unstable();
+
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(block_ranges.len());
+
+    for (start, end) in block_ranges {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= last_end.saturating_add(1) => {
+                *last_end = (*last_end).max(end)
+            }
+            _ => merged.push((start, end)),
+        }
+    }
+
+    merged
+}
+
+/// Parts of inclusive block range, which are not covered by given sorted, non-overlapping ranges
+pub fn subtract_block_ranges(
+    (r_start, r_end): (u64, u64),
+    covered_block_ranges: impl IntoIterator<Item = (u64, u64)>,
+) -> Vec<(u64, u64)> {
+    let mut uncovered = vec![];
+
+    if r_start > r_end {
+        return uncovered;
+    }
+
+    // First block, which is not known to be covered yet
+    let mut next = r_start;
+
+    for (start, end) in covered_block_ranges {
+        if end < next {
+            continue;
+        }
+        if start > r_end {
+            break;
+        }
+        if start > next {
+            uncovered.push((next, start - 1));
+        }
+        match end.checked_add(1) {
+            Some(after_end) if after_end <= r_end => next = after_end,
+            _ => return uncovered,
+        }
+    }
+
+    uncovered.push((next, r_end));
+
+    uncovered
 }
 
 impl Query {
-    pub async fn get_transactions_count_since_block_for_selected_address(
+    pub async fn get_internal_transactions_count_since_block_for_selected_address(
         db: &DbConn,
+        chain_id: u64,
         starting_block_id: u64,
         address: String,
     ) -> Result<u64, DbErr> {
-        Transactions::find()
+        InternalTransactions::find()
+            .filter(internal_transactions::Column::ChainId.eq(chain_id))
             .filter(
                 Condition::any()
-                    .add(transactions::Column::AddressFrom.eq(&address))
-                    .add(transactions::Column::AddressTo.eq(&address)),
+                    .add(internal_transactions::Column::AddressFrom.eq(&address))
+                    .add(internal_transactions::Column::AddressTo.eq(&address)),
             )
-            .filter(transactions::Column::BlockNumber.gt(starting_block_id))
+            .filter(internal_transactions::Column::BlockNumber.gte(starting_block_id))
             .count(db)
             .await
     }
 
-    /// Retrieves transactions block numbers since starting block for selected address.
-    pub async fn get_block_numbers_since_block_for_selected_address(
+    /// If ok, returns (internal transaction models, num pages).
+    pub async fn find_internal_transactions_in_page(
         db: &DbConn,
+        chain_id: u64,
+        address: String,
+        starting_block_id: u64,
+        page: u64,
+        transactions_per_page: u64,
+    ) -> Result<(Vec<internal_transactions::Model>, u64), DbErr> {
+        // Setup paginator
+        let paginator = InternalTransactions::find()
+            .filter(internal_transactions::Column::ChainId.eq(chain_id))
+            .filter(internal_transactions::Column::BlockNumber.gte(starting_block_id))
+            .filter(
+                Condition::any()
+                    .add(internal_transactions::Column::AddressFrom.eq(&address))
+                    .add(internal_transactions::Column::AddressTo.eq(&address)),
+            )
+            .order_by_asc(internal_transactions::Column::BlockNumber)
+            .order_by_asc(internal_transactions::Column::ParentTxId)
+            .order_by_asc(internal_transactions::Column::TraceId)
+            .paginate(db, transactions_per_page);
+        let num_pages = paginator.num_pages().await?;
+
+        // Fetch paginated internal transactions
+        paginator
+            .fetch_page(page.saturating_sub(1))
+            .await
+            .map(|p| (p, num_pages))
+    }
+
+    /// Builds token transfers query for selected address and, optionally, selected token contract.
+    fn token_transfers_since_block_for_selected_address(
+        chain_id: u64,
         address: &str,
+        token: Option<&str>,
         starting_block_id: u64,
-    ) -> Result<BTreeSet<u64>, DbErr> {
-        let last_transaction: Vec<u64> = Transactions::find()
-            .filter(transactions::Column::BlockNumber.gte(starting_block_id))
+    ) -> Select<TokenTransfers> {
+        let mut query = TokenTransfers::find()
+            .filter(token_transfers::Column::ChainId.eq(chain_id))
+            .filter(token_transfers::Column::BlockNumber.gte(starting_block_id))
             .filter(
                 Condition::any()
-                    .add(transactions::Column::AddressFrom.eq(address))
-                    .add(transactions::Column::AddressTo.eq(address)),
+                    .add(token_transfers::Column::AddressFrom.eq(address))
+                    .add(token_transfers::Column::AddressTo.eq(address)),
+            );
+
+        if let Some(token) = token {
+            query = query.filter(token_transfers::Column::ContractAddress.eq(token));
+        }
+
+        query
+    }
+
+    pub async fn get_token_transfers_count_since_block_for_selected_address(
+        db: &DbConn,
+        chain_id: u64,
+        starting_block_id: u64,
+        address: String,
+        token: Option<String>,
+    ) -> Result<u64, DbErr> {
+        Self::token_transfers_since_block_for_selected_address(
+            chain_id,
+            &address,
+            token.as_deref(),
+            starting_block_id,
+        )
+        .count(db)
+        .await
+    }
+
+    /// If ok, returns (token transfer models along with their token metadata, num pages).
+    pub async fn find_token_transfers_in_page(
+        db: &DbConn,
+        chain_id: u64,
+        address: String,
+        token: Option<String>,
+        starting_block_id: u64,
+        page: u64,
+        transfers_per_page: u64,
+    ) -> Result<(Vec<(token_transfers::Model, Option<tokens::Model>)>, u64), DbErr> {
+        // Setup paginator
+        let paginator = Self::token_transfers_since_block_for_selected_address(
+            chain_id,
+            &address,
+            token.as_deref(),
+            starting_block_id,
+        )
+        .find_also_related(Tokens)
+        .order_by_asc(token_transfers::Column::BlockNumber)
+        .order_by_asc(token_transfers::Column::TxId)
+        .order_by_asc(token_transfers::Column::LogIndex)
+        .paginate(db, transfers_per_page);
+        let num_pages = paginator.num_pages().await?;
+
+        // Fetch paginated token transfers
+        paginator
+            .fetch_page(page.saturating_sub(1))
+            .await
+            .map(|p| (p, num_pages))
+    }
+
+    /// Retrieves metadata of all tokens, transferred from or to selected address.
+    pub async fn find_tokens_for_selected_address(
+        db: &DbConn,
+        chain_id: u64,
+        address: &str,
+    ) -> Result<Vec<tokens::Model>, DbErr> {
+        Tokens::find()
+            .filter(tokens::Column::ChainId.eq(chain_id))
+            .filter(
+                tokens::Column::ContractAddress.in_subquery(
+                    TokenTransfers::find()
+                        .select_only()
+                        .column(token_transfers::Column::ContractAddress)
+                        .filter(token_transfers::Column::ChainId.eq(chain_id))
+                        .filter(
+                            Condition::any()
+                                .add(token_transfers::Column::AddressFrom.eq(address))
+                                .add(token_transfers::Column::AddressTo.eq(address)),
+                        )
+                        .into_query(),
+                ),
             )
-            .select_only()
-            .column(transactions::Column::BlockNumber)
-            .into_values::<_, QueryAs>()
+            .order_by_asc(tokens::Column::Symbol)
             .all(db)
-            .await?;
+            .await
+    }
 
-        Ok(last_transaction.into_iter().collect())
+    /// Builds NFT transfers query for selected address.
+    fn nft_transfers_for_selected_address(chain_id: u64, address: &str) -> Select<NftTransfers> {
+        NftTransfers::find()
+            .filter(nft_transfers::Column::ChainId.eq(chain_id))
+            .filter(
+                Condition::any()
+                    .add(nft_transfers::Column::AddressFrom.eq(address))
+                    .add(nft_transfers::Column::AddressTo.eq(address)),
+            )
     }
 
-    /// If ok, returns (post models, num pages).
-    pub async fn find_transactions_in_page(
+    /// If ok, returns (NFT transfer models, num pages).
+    pub async fn find_nft_transfers_in_page(
         db: &DbConn,
+        chain_id: u64,
         address: String,
-        starting_block_id: u64,
         page: u64,
-        transactions_per_page: u64,
-    ) -> Result<(Vec<transactions::Model>, u64), DbErr> {
+        transfers_per_page: u64,
+    ) -> Result<(Vec<nft_transfers::Model>, u64), DbErr> {
         // Setup paginator
-        let paginator = Transactions::find()
-            .filter(transactions::Column::BlockNumber.gte(starting_block_id))
+        let paginator = Self::nft_transfers_for_selected_address(chain_id, &address)
+            .order_by_asc(nft_transfers::Column::BlockNumber)
+            .order_by_asc(nft_transfers::Column::TxId)
+            .order_by_asc(nft_transfers::Column::LogIndex)
+            .paginate(db, transfers_per_page);
+        let num_pages = paginator.num_pages().await?;
+
+        // Fetch paginated NFT transfers
+        paginator
+            .fetch_page(page.saturating_sub(1))
+            .await
+            .map(|p| (p, num_pages))
+    }
+
+    /// Rebuilds NFTs held by selected address at the end of given block, or latest ones, by replaying crawled transfers.
+    /// NFTs received before the earliest crawled block are not taken into account,
+    /// holdings are marked inconsistent if they are sent later.
+    pub async fn get_nft_holdings_at_block(
+        db: &DbConn,
+        chain_id: u64,
+        address: &str,
+        block_number: Option<u64>,
+    ) -> Result<NftHoldings, DbErr> {
+        // Log index is unique within block, so transfers are replayed in execution order
+        let nft_transfers = Self::nft_transfers_for_selected_address(chain_id, address)
+            .apply_if(block_number, |query, block_number| {
+                query.filter(nft_transfers::Column::BlockNumber.lte(block_number))
+            })
+            .order_by_asc(nft_transfers::Column::BlockNumber)
+            .order_by_asc(nft_transfers::Column::LogIndex)
+            .all(db)
+            .await?;
+
+        let mut holdings: BTreeMap<(String, U256), NftHolding> = BTreeMap::new();
+        let mut consistent = true;
+
+        for nft_transfer in nft_transfers {
+            let holding = holdings
+                .entry((nft_transfer.contract_address.clone(), nft_transfer.token_id))
+                .or_insert_with(|| NftHolding {
+                    contract_address: nft_transfer.contract_address.clone(),
+                    token_id: nft_transfer.token_id,
+                    standard: nft_transfer.standard,
+                    quantity: U256::zero(),
+                });
+
+            // Self transfers don't change holdings
+            if nft_transfer.address_to.eq_ignore_ascii_case(address) {
+                add_amount(&mut holding.quantity, nft_transfer.quantity)?;
+            }
+            if nft_transfer.address_from.eq_ignore_ascii_case(address) {
+                match holding.quantity.checked_sub(nft_transfer.quantity) {
+                    Some(quantity) => holding.quantity = quantity,
+                    None => {
+                        holding.quantity = U256::zero();
+                        consistent = false;
+                    }
+                }
+            }
+        }
+
+        Ok(NftHoldings {
+            holdings: holdings
+                .into_values()
+                .filter(|holding| !holding.quantity.is_zero())
+                .collect(),
+            consistent,
+        })
+    }
+
+    /// Rebuilds ETH balance of selected address at given block or date.
+    /// Balance is replayed back from `anchor_balance`, known balance at the end of `anchor_block`:
+    /// inflows after requested point are subtracted, outflows and fees are added back.
+    pub async fn balance_at(
+        db: &DbConn,
+        chain_id: u64,
+        address: &str,
+        at: BlockOrDate,
+        anchor_block: u64,
+        anchor_balance: U256,
+    ) -> Result<BalanceAt, DbErr> {
+        let (transactions_since, internal_transactions_since) = match at {
+            BlockOrDate::Block(block_number) => (
+                Condition::all().add(transactions::Column::BlockNumber.gt(block_number)),
+                Condition::all().add(internal_transactions::Column::BlockNumber.gt(block_number)),
+            ),
+            BlockOrDate::Date(date) => {
+                let next_day_start = date
+                    .succ_opt()
+                    .and_then(|next_day| next_day.and_hms_opt(0, 0, 0))
+                    .ok_or_else(|| DbErr::Custom(format!("Date out of range: {date}")))?;
+                (
+                    Condition::all().add(transactions::Column::DateTime.gte(next_day_start)),
+                    Condition::all()
+                        .add(internal_transactions::Column::DateTime.gte(next_day_start)),
+                )
+            }
+        };
+
+        let transactions = Transactions::find()
+            .filter(transactions::Column::ChainId.eq(chain_id))
+            .filter(transactions_since)
+            .filter(transactions::Column::BlockNumber.lte(anchor_block))
             .filter(
                 Condition::any()
-                    .add(transactions::Column::AddressFrom.eq(&address))
-                    .add(transactions::Column::AddressTo.eq(&address)),
+                    .add(transactions::Column::AddressFrom.eq(address))
+                    .add(transactions::Column::AddressTo.eq(address))
+                    .add(transactions::Column::ContractAddress.eq(address)),
             )
-            .order_by_asc(transactions::Column::TxId)
+            .all(db)
+            .await?;
+
+        let internal_transactions = InternalTransactions::find()
+            .filter(internal_transactions::Column::ChainId.eq(chain_id))
+            .filter(internal_transactions_since)
+            .filter(internal_transactions::Column::BlockNumber.lte(anchor_block))
+            .filter(internal_transactions::Column::IsError.eq(false))
+            .filter(
+                Condition::any()
+                    .add(internal_transactions::Column::AddressFrom.eq(address))
+                    .add(internal_transactions::Column::AddressTo.eq(address)),
+            )
+            .all(db)
+            .await?;
+
+        let mut received_since = U256::zero();
+        let mut sent_since = U256::zero();
+        let mut fees_since = U256::zero();
+        let mut burnt_fees_since = U256::zero();
+        let mut tips_since = U256::zero();
+
+        for transaction in transactions {
+            let is_failed = transaction.is_failed();
+
+            if !is_failed
+                && transaction
+                    .recipient()
+                    .is_some_and(|recipient| recipient.eq_ignore_ascii_case(address))
+            {
+                add_amount(&mut received_since, transaction.value)?;
+            }
+            if transaction.address_from.eq_ignore_ascii_case(address) {
+                if !is_failed {
+                    add_amount(&mut sent_since, transaction.value)?;
+                }
+                // Failed transactions are charged their fee too
+                add_amount(&mut fees_since, transaction.tx_fee)?;
+                if let (Some(burnt_fee), Some(priority_fee)) =
+                    (transaction.burnt_fee(), transaction.priority_fee())
+                {
+                    add_amount(&mut burnt_fees_since, burnt_fee)?;
+                    add_amount(&mut tips_since, priority_fee)?;
+                }
+            }
+        }
+
+        for internal_transaction in internal_transactions {
+            if internal_transaction
+                .address_to
+                .eq_ignore_ascii_case(address)
+            {
+                add_amount(&mut received_since, internal_transaction.value)?;
+            }
+            if internal_transaction
+                .address_from
+                .eq_ignore_ascii_case(address)
+            {
+                add_amount(&mut sent_since, internal_transaction.value)?;
+            }
+        }
+
+        let mut outflows = anchor_balance;
+        add_amount(&mut outflows, sent_since)?;
+        add_amount(&mut outflows, fees_since)?;
+        let balance = outflows.checked_sub(received_since);
+
+        Ok(BalanceAt {
+            balance: balance.unwrap_or_default(),
+            anchor_balance,
+            anchor_block,
+            received_since,
+            sent_since,
+            fees_since,
+            burnt_fees_since,
+            tips_since,
+            consistent: balance.is_some(),
+        })
+    }
+
+    /// Summary, top counterparties (at most `limit` of each kind) and monthly activity
+    /// of selected address, built in one pass over its transactions chunk by chunk
+    pub async fn get_address_activity(
+        db: &DbConn,
+        chain_id: u64,
+        address: &str,
+        limit: usize,
+    ) -> Result<AddressActivity, DbErr> {
+        let mut summary = AddressSummary::default();
+        let mut counterparties: HashMap<String, Counterparty> = HashMap::new();
+        let mut months: BTreeMap<String, MonthlyActivity> = BTreeMap::new();
+        let mut after: Option<TransactionKey> = None;
+
+        loop {
+            let transactions = Self::find_filtered_transactions_chunk(
+                db,
+                chain_id,
+                address,
+                &TransactionFilter::default(),
+                after.as_ref(),
+                ACTIVITY_CHUNK_SIZE,
+            )
+            .await?;
+
+            for transaction in &transactions {
+                let direction = Direction::of(&transaction.transaction, address);
+
+                summary.add(transaction, direction)?;
+
+                if let Some((counterparty_address, is_sent)) =
+                    Counterparty::of(&transaction.transaction, direction)
+                {
+                    counterparties
+                        .entry(counterparty_address.to_lowercase())
+                        .or_insert_with(|| Counterparty::new(counterparty_address))
+                        .add(&transaction.transaction, is_sent)?;
+                }
+
+                let month = transaction
+                    .transaction
+                    .date_time
+                    .format("%Y-%m")
+                    .to_string();
+                months
+                    .entry(month.clone())
+                    .or_insert_with(|| MonthlyActivity::new(month))
+                    .add(&transaction.transaction, direction)?;
+            }
+
+            match transactions.last() {
+                Some(last) if transactions.len() as u64 == ACTIVITY_CHUNK_SIZE => {
+                    after = Some(TransactionKey::from(&last.transaction));
+                }
+                _ => break,
+            }
+        }
+
+        let mut by_volume: Vec<Counterparty> = counterparties.into_values().collect();
+        let mut by_count = by_volume.clone();
+
+        // Ties are broken by address, so that order is stable
+        by_volume.sort_by(|a, b| {
+            b.volume
+                .cmp(&a.volume)
+                .then_with(|| a.address.cmp(&b.address))
+        });
+        by_volume.truncate(limit);
+        by_count.sort_by(|a, b| {
+            b.transactions_count
+                .cmp(&a.transactions_count)
+                .then_with(|| a.address.cmp(&b.address))
+        });
+        by_count.truncate(limit);
+
+        Ok(AddressActivity {
+            summary,
+            top_counterparties: TopCounterparties {
+                by_volume,
+                by_count,
+            },
+            monthly_activity: months.into_values().collect(),
+        })
+    }
+
+    pub async fn find_crawl_job_by_id(
+        db: &DbConn,
+        id: i32,
+    ) -> Result<Option<crawl_jobs::Model>, DbErr> {
+        CrawlJobs::find_by_id(id).one(db).await
+    }
+
+    /// Jobs, which were not finished yet, oldest first
+    pub async fn find_unfinished_crawl_jobs(db: &DbConn) -> Result<Vec<crawl_jobs::Model>, DbErr> {
+        CrawlJobs::find()
+            .filter(
+                crawl_jobs::Column::Status
+                    .is_in([CrawlJobStatus::Pending, CrawlJobStatus::Running]),
+            )
+            .order_by_asc(crawl_jobs::Column::Id)
+            .all(db)
+            .await
+    }
+
+    /// Latest unfinished job for selected address on given chain
+    pub async fn find_active_crawl_job_for_selected_address(
+        db: &DbConn,
+        chain_id: u64,
+        address: &str,
+    ) -> Result<Option<crawl_jobs::Model>, DbErr> {
+        CrawlJobs::find()
+            .filter(crawl_jobs::Column::ChainId.eq(chain_id))
+            .filter(crawl_jobs::Column::Address.eq(address))
+            .filter(
+                crawl_jobs::Column::Status
+                    .is_in([CrawlJobStatus::Pending, CrawlJobStatus::Running]),
+            )
+            .order_by_desc(crawl_jobs::Column::Id)
+            .one(db)
+            .await
+    }
+
+    /// Transactions of selected address within inclusive block range, which lack fee breakdown,
+    /// e.g. because they were crawled before JSON-RPC node was configured. At most `limit` of them.
+    pub async fn find_transactions_without_fee_details(
+        db: &DbConn,
+        chain_id: u64,
+        address: &str,
+        (r_start, r_end): (u64, u64),
+        limit: u64,
+    ) -> Result<Vec<transactions::Model>, DbErr> {
+        let filter = TransactionFilter {
+            from_block: Some(r_start),
+            to_block: Some(r_end),
+            ..Default::default()
+        };
+
+        Transactions::find()
+            .filter(transactions::Column::ChainId.eq(chain_id))
+            .filter(filter.condition(address))
+            .filter(transactions::Column::EffectiveGasPrice.is_null())
+            .order_by_asc(transactions::Column::BlockNumber)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    /// Block ranges within given range, which were never fetched for selected address on given chain
+    /// from given source
+    pub async fn find_uncovered_block_ranges(
+        db: &DbConn,
+        chain_id: u64,
+        address: &str,
+        source: CoverageSource,
+        (r_start, r_end): (u64, u64),
+    ) -> Result<Vec<(u64, u64)>, DbErr> {
+        let covered_block_ranges = CrawlCoverage::find()
+            .filter(crawl_coverage::Column::ChainId.eq(chain_id))
+            .filter(crawl_coverage::Column::Address.eq(address))
+            .filter(crawl_coverage::Column::Source.eq(source))
+            .filter(crawl_coverage::Column::StartBlock.lte(r_end))
+            .filter(crawl_coverage::Column::EndBlock.gte(r_start))
+            .order_by_asc(crawl_coverage::Column::StartBlock)
+            .all(db)
+            .await?;
+
+        Ok(subtract_block_ranges(
+            (r_start, r_end),
+            covered_block_ranges
+                .into_iter()
+                .map(|covered| (covered.start_block.0, covered.end_block.0)),
+        ))
+    }
+
+    /// Transactions of selected address on given chain, which match filter, in given order
+    fn filtered_transactions(
+        chain_id: u64,
+        address: &str,
+        filter: &TransactionFilter,
+        sort: &TransactionSort,
+    ) -> Select<Transactions> {
+        let order = Order::from(sort.order);
+        let select = Transactions::find()
+            .filter(transactions::Column::ChainId.eq(chain_id))
+            .filter(filter.condition(address));
+
+        let select = match sort.sort {
+            SortKey::Block => select,
+            SortKey::Time => select.order_by(transactions::Column::DateTime, order.clone()),
+            SortKey::Value => select.order_by(transactions::Column::Value, order.clone()),
+            SortKey::Fee => select.order_by(transactions::Column::TxFee, order.clone()),
+        };
+
+        select
+            .order_by(transactions::Column::BlockNumber, order.clone())
+            .order_by(transactions::Column::TransactionIndex, order.clone())
+            .order_by(transactions::Column::TxId, order)
+    }
+
+    /// If ok, returns (transactions matching filter in given order, num items, num pages)
+    pub async fn find_filtered_transactions_in_page(
+        db: &DbConn,
+        chain_id: u64,
+        address: &str,
+        filter: &TransactionFilter,
+        sort: &TransactionSort,
+        page: u64,
+        transactions_per_page: u64,
+    ) -> Result<(Vec<TransactionWithFees>, u64, u64), DbErr> {
+        let paginator = Self::filtered_transactions(chain_id, address, filter, sort)
             .paginate(db, transactions_per_page);
-        let num_pages = paginator.num_pages().await?;
+        let ItemsAndPagesNumber {
+            number_of_items,
+            number_of_pages,
+        } = paginator.num_items_and_pages().await?;
+
+        let transactions = paginator.fetch_page(page.saturating_sub(1)).await?;
+
+        Ok((
+            transactions
+                .into_iter()
+                .map(TransactionWithFees::from)
+                .collect(),
+            number_of_items,
+            number_of_pages,
+        ))
+    }
+
+    /// Chunk of transactions matching filter in chain order, which follow transaction at `after`
+    /// or start from the first one. Unlike pages, chunks don't count matching transactions
+    /// and don't skip rows by offset, so reading a chunk costs the same anywhere in the history.
+    pub async fn find_filtered_transactions_chunk(
+        db: &DbConn,
+        chain_id: u64,
+        address: &str,
+        filter: &TransactionFilter,
+        after: Option<&TransactionKey>,
+        chunk_size: u64,
+    ) -> Result<Vec<TransactionWithFees>, DbErr> {
+        // transaction index is missing in some rows, those sort as the first in their block
+        let transaction_index = || {
+            Expr::expr(Func::coalesce([
+                Expr::col(transactions::Column::TransactionIndex).into(),
+                Expr::val(0u64).into(),
+            ]))
+        };
+
+        let mut select = Transactions::find()
+            .filter(transactions::Column::ChainId.eq(chain_id))
+            .filter(filter.condition(address));
+
+        if let Some(after) = after {
+            select = select.filter(
+                Condition::any()
+                    .add(transactions::Column::BlockNumber.gt(after.block_number))
+                    .add(
+                        Condition::all()
+                            .add(transactions::Column::BlockNumber.eq(after.block_number))
+                            .add(
+                                Condition::any()
+                                    .add(transaction_index().gt(after.transaction_index))
+                                    .add(
+                                        Condition::all()
+                                            .add(transaction_index().eq(after.transaction_index))
+                                            .add(transactions::Column::TxId.gt(&after.tx_id)),
+                                    ),
+                            ),
+                    ),
+            );
+        }
+
+        let transactions = select
+            .order_by_asc(transactions::Column::BlockNumber)
+            .order_by_asc(transaction_index())
+            .order_by_asc(transactions::Column::TxId)
+            .limit(chunk_size)
+            .all(db)
+            .await?;
+
+        Ok(transactions
+            .into_iter()
+            .map(TransactionWithFees::from)
+            .collect())
+    }
+
+    pub async fn find_transaction_by_hash(
+        db: &DbConn,
+        chain_id: u64,
+        tx_hash: &str,
+    ) -> Result<Option<TransactionWithFees>, DbErr> {
+        Ok(
+            Transactions::find_by_id((U64(chain_id), tx_hash.to_owned()))
+                .one(db)
+                .await?
+                .map(TransactionWithFees::from),
+        )
+    }
+
+    /// Latest crawl jobs, optionally of selected chain and address only
+    pub async fn find_latest_crawl_jobs(
+        db: &DbConn,
+        chain_id: Option<u64>,
+        address: Option<&str>,
+        limit: u64,
+    ) -> Result<Vec<crawl_jobs::Model>, DbErr> {
+        let mut select = CrawlJobs::find();
+
+        if let Some(chain_id) = chain_id {
+            select = select.filter(crawl_jobs::Column::ChainId.eq(chain_id));
+        }
+
+        if let Some(address) = address {
+            select = select.filter(crawl_jobs::Column::Address.eq(address));
+        }
+
+        select
+            .order_by_desc(crawl_jobs::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_watched_addresses(
+        db: &DbConn,
+    ) -> Result<Vec<watched_addresses::Model>, DbErr> {
+        WatchedAddresses::find()
+            .order_by_asc(watched_addresses::Column::Id)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_active_watched_addresses(
+        db: &DbConn,
+    ) -> Result<Vec<watched_addresses::Model>, DbErr> {
+        WatchedAddresses::find()
+            .filter(watched_addresses::Column::Status.eq(WatchStatus::Active))
+            .order_by_asc(watched_addresses::Column::Id)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_watched_address_by_address(
+        db: &DbConn,
+        chain_id: u64,
+        address: &str,
+    ) -> Result<Option<watched_addresses::Model>, DbErr> {
+        WatchedAddresses::find()
+            .filter(watched_addresses::Column::ChainId.eq(chain_id))
+            .filter(watched_addresses::Column::Address.eq(address))
+            .one(db)
+            .await
+    }
+
+    /// Distinct hashes of blocks of given chain since given block, which were recorded for blocks
+    /// or crawled transactions and transfers were included in, in ascending block order.
+    /// Rows crawled without block hash are skipped. One block may have been seen with different hashes.
+    pub async fn find_block_hashes_since_block(
+        db: &DbConn,
+        chain_id: u64,
+        starting_block_number: u64,
+    ) -> Result<Vec<(u64, String)>, DbErr> {
+        let mut block_hashes = BTreeSet::new();
+
+        block_hashes.extend(
+            Self::find_block_hashes_of::<BlockHashes>(
+                db,
+                (
+                    block_hashes::Column::ChainId,
+                    block_hashes::Column::BlockNumber,
+                    block_hashes::Column::BlockHash,
+                ),
+                chain_id,
+                starting_block_number,
+            )
+            .await?,
+        );
+
+        block_hashes.extend(
+            Self::find_block_hashes_of::<Transactions>(
+                db,
+                (
+                    transactions::Column::ChainId,
+                    transactions::Column::BlockNumber,
+                    transactions::Column::BlockHash,
+                ),
+                chain_id,
+                starting_block_number,
+            )
+            .await?,
+        );
+        block_hashes.extend(
+            Self::find_block_hashes_of::<InternalTransactions>(
+                db,
+                (
+                    internal_transactions::Column::ChainId,
+                    internal_transactions::Column::BlockNumber,
+                    internal_transactions::Column::BlockHash,
+                ),
+                chain_id,
+                starting_block_number,
+            )
+            .await?,
+        );
+        block_hashes.extend(
+            Self::find_block_hashes_of::<TokenTransfers>(
+                db,
+                (
+                    token_transfers::Column::ChainId,
+                    token_transfers::Column::BlockNumber,
+                    token_transfers::Column::BlockHash,
+                ),
+                chain_id,
+                starting_block_number,
+            )
+            .await?,
+        );
+        block_hashes.extend(
+            Self::find_block_hashes_of::<NftTransfers>(
+                db,
+                (
+                    nft_transfers::Column::ChainId,
+                    nft_transfers::Column::BlockNumber,
+                    nft_transfers::Column::BlockHash,
+                ),
+                chain_id,
+                starting_block_number,
+            )
+            .await?,
+        );
+
+        Ok(block_hashes.into_iter().collect())
+    }
+
+    /// Distinct (block number, block hash) pairs of rows of single table
+    /// Numbers of blocks of given chain within block range, whose hashes are recorded
+    pub async fn find_recorded_block_numbers(
+        db: &DbConn,
+        chain_id: u64,
+        (start, end): (u64, u64),
+    ) -> Result<Vec<u64>, DbErr> {
+        BlockHashes::find()
+            .select_only()
+            .column(block_hashes::Column::BlockNumber)
+            .filter(block_hashes::Column::ChainId.eq(chain_id))
+            .filter(block_hashes::Column::BlockNumber.between(start, end))
+            .order_by_asc(block_hashes::Column::BlockNumber)
+            .into_tuple::<U64>()
+            .all(db)
+            .await
+            .map(|block_numbers| {
+                block_numbers
+                    .into_iter()
+                    .map(|block_number| block_number.0)
+                    .collect()
+            })
+    }
+
+    async fn find_block_hashes_of<E: EntityTrait>(
+        db: &DbConn,
+        (chain_id_column, block_number_column, block_hash_column): (
+            E::Column,
+            E::Column,
+            E::Column,
+        ),
+        chain_id: u64,
+        starting_block_number: u64,
+    ) -> Result<Vec<(u64, String)>, DbErr> {
+        E::find()
+            .select_only()
+            .column(block_number_column)
+            .column(block_hash_column)
+            .distinct()
+            .filter(chain_id_column.eq(chain_id))
+            .filter(block_number_column.gte(starting_block_number))
+            .filter(block_hash_column.is_not_null())
+            .into_tuple::<(U64, String)>()
+            .all(db)
+            .await
+            .map(|block_hashes| {
+                block_hashes
+                    .into_iter()
+                    .map(|(block_number, block_hash)| (block_number.0, block_hash.to_lowercase()))
+                    .collect()
+            })
+    }
+}
+
+/// Adds amount to total. Amounts are bounded by 256 bits, so a sum out of range is an error.
+fn add_amount(total: &mut U256, amount: U256) -> Result<(), DbErr> {
+    *total = total
+        .checked_add(amount)
+        .ok_or_else(|| DbErr::Custom("Sum of amounts doesn't fit into 256 bits".to_string()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_block_ranges_joins_overlapping_and_adjacent_ranges() {
+        assert_eq!(merge_block_ranges(vec![]), vec![]);
+        assert_eq!(merge_block_ranges(vec![(5, 5)]), vec![(5, 5)]);
+        // adjacent
+        assert_eq!(merge_block_ranges(vec![(11, 20), (1, 10)]), vec![(1, 20)]);
+        // overlapping
+        assert_eq!(merge_block_ranges(vec![(1, 10), (5, 15)]), vec![(1, 15)]);
+        // contained
+        assert_eq!(merge_block_ranges(vec![(1, 100), (10, 20)]), vec![(1, 100)]);
+        assert_eq!(merge_block_ranges(vec![(10, 20), (1, 100)]), vec![(1, 100)]);
+        // single blocks
+        assert_eq!(
+            merge_block_ranges(vec![(3, 3), (1, 1), (2, 2), (5, 5)]),
+            vec![(1, 3), (5, 5)]
+        );
+        // gap of one block is kept
+        assert_eq!(
+            merge_block_ranges(vec![(1, 10), (12, 20)]),
+            vec![(1, 10), (12, 20)]
+        );
+    }
+
+    #[test]
+    fn merge_block_ranges_handles_last_block() {
+        assert_eq!(
+            merge_block_ranges(vec![(u64::MAX, u64::MAX), (0, u64::MAX - 1)]),
+            vec![(0, u64::MAX)]
+        );
+        assert_eq!(
+            merge_block_ranges(vec![(10, u64::MAX), (u64::MAX, u64::MAX)]),
+            vec![(10, u64::MAX)]
+        );
+    }
+
+    #[test]
+    fn subtract_block_ranges_returns_uncovered_parts() {
+        assert_eq!(subtract_block_ranges((1, 10), []), vec![(1, 10)]);
+        assert_eq!(subtract_block_ranges((10, 1), []), vec![]);
+        assert_eq!(subtract_block_ranges((1, 10), [(1, 10)]), vec![]);
+        // covering range contains the whole range
+        assert_eq!(subtract_block_ranges((5, 6), [(1, 10)]), vec![]);
+        // covered ranges within the range
+        assert_eq!(
+            subtract_block_ranges((1, 20), [(3, 5), (10, 10)]),
+            vec![(1, 2), (6, 9), (11, 20)]
+        );
+        // adjacent covered ranges leave no gap between them
+        assert_eq!(
+            subtract_block_ranges((1, 20), [(1, 5), (6, 10)]),
+            vec![(11, 20)]
+        );
+        // covered ranges overlapping range bounds, or outside of it
+        assert_eq!(
+            subtract_block_ranges((10, 20), [(1, 2), (5, 12), (18, 30)]),
+            vec![(13, 17)]
+        );
+        // single block range
+        assert_eq!(subtract_block_ranges((7, 7), [(1, 6)]), vec![(7, 7)]);
+        assert_eq!(subtract_block_ranges((7, 7), [(7, 7)]), vec![]);
+    }
 
-        // Fetch paginated transactions
-        paginator.fetch_page(page - 1).await.map(|p| (p, num_pages))
+    #[test]
+    fn subtract_block_ranges_handles_last_block() {
+        assert_eq!(
+            subtract_block_ranges((0, u64::MAX), [(0, u64::MAX)]),
+            vec![]
+        );
+        assert_eq!(
+            subtract_block_ranges((0, u64::MAX), [(10, u64::MAX)]),
+            vec![(0, 9)]
+        );
+        assert_eq!(
+            subtract_block_ranges((0, u64::MAX), [(0, 10)]),
+            vec![(11, u64::MAX)]
+        );
+        assert_eq!(
+            subtract_block_ranges((u64::MAX, u64::MAX), []),
+            vec![(u64::MAX, u64::MAX)]
+        );
     }
 }
@@ -0,0 +1,286 @@
+use actix_example_service::{
+    sea_orm::{ConnectOptions, Database, DbConn},
+    BlockOrDate, Mutation, Query, TransactionFilter, TransactionKey, TransactionSort,
+};
+use entity::{crawl_coverage::CoverageSource, transactions, u256::U256, unsigned::U64};
+use migration::{Migrator, MigratorTrait};
+use std::str::FromStr;
+
+const CHAIN_ID: u64 = 1;
+const ADDRESS: &str = "0x00000000000000000000000000000000000000aa";
+const COUNTERPARTY: &str = "0x00000000000000000000000000000000000000bb";
+
+// Largest block number, which every supported database can store
+const MAX_STORED_NUMBER: u64 = i64::MAX as u64;
+
+/// In-memory database with all migrations applied. Every connection to `sqlite::memory:`
+/// opens a database of its own, so the pool is limited to one connection.
+async fn migrated_db() -> DbConn {
+    let mut options = ConnectOptions::new("sqlite::memory:");
+    options.max_connections(1);
+
+    let db = Database::connect(options).await.unwrap();
+    Migrator::up(&db, None).await.unwrap();
+
+    db
+}
+
+fn transaction(
+    tx_id: &str,
+    block_number: u64,
+    transaction_index: Option<u64>,
+) -> transactions::Model {
+    transactions::Model {
+        chain_id: U64(CHAIN_ID),
+        tx_id: tx_id.to_string(),
+        block_number: U64(block_number),
+        block_hash: Some(format!("0xhash{block_number}")),
+        date_time: chrono::NaiveDate::from_ymd_opt(2023, 10, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap(),
+        address_from: ADDRESS.to_string(),
+        address_to: Some(COUNTERPARTY.to_string()),
+        value: U256::from(1_000_000_000_000_000_000),
+        tx_fee: U256::from(21_000),
+        contract_address: None,
+        is_error: false,
+        receipt_status: Some(true),
+        nonce: Some(U64(0)),
+        transaction_index: transaction_index.map(U64),
+        gas: Some(U64(21_000)),
+        gas_used: Some(U64(21_000)),
+        gas_price: Some(U256::from(1)),
+        cumulative_gas_used: Some(U64(21_000)),
+        input: Some("0x".to_string()),
+        method_id: Some("0x".to_string()),
+        function_name: None,
+        tx_type: None,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
+        effective_gas_price: None,
+        base_fee_per_gas: None,
+        blob_gas_used: None,
+        blob_gas_price: None,
+    }
+}
+
+#[tokio::test]
+async fn saved_transactions_are_found() {
+    let db = migrated_db().await;
+
+    let mut large = transaction("0x02", MAX_STORED_NUMBER, Some(3));
+    large.value = U256::from_str(
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935",
+    )
+    .unwrap();
+    let saved = vec![transaction("0x01", 100, Some(0)), large.clone()];
+
+    Mutation::save_transactions(&db, saved.clone())
+        .await
+        .unwrap();
+    // Saving the same transactions again keeps stored rows
+    Mutation::save_transactions(&db, saved).await.unwrap();
+
+    let found = Query::find_transaction_by_hash(&db, CHAIN_ID, "0x02")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.transaction, large);
+
+    let (transactions, total, num_pages) = Query::find_filtered_transactions_in_page(
+        &db,
+        CHAIN_ID,
+        ADDRESS,
+        &TransactionFilter::default(),
+        &TransactionSort::default(),
+        1,
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!((total, num_pages), (2, 1));
+    assert_eq!(
+        transactions
+            .iter()
+            .map(|transaction| transaction.transaction.tx_id.as_str())
+            .collect::<Vec<_>>(),
+        ["0x01", "0x02"]
+    );
+
+    let filter = TransactionFilter {
+        from_block: Some(MAX_STORED_NUMBER),
+        ..Default::default()
+    };
+    let (transactions, total, _) = Query::find_filtered_transactions_in_page(
+        &db,
+        CHAIN_ID,
+        ADDRESS,
+        &filter,
+        &TransactionSort::default(),
+        1,
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!(total, 1);
+    assert_eq!(transactions[0].transaction.tx_id, "0x02");
+}
+
+#[tokio::test]
+async fn export_chunks_continue_after_last_key() {
+    let db = migrated_db().await;
+
+    Mutation::save_transactions(
+        &db,
+        vec![
+            transaction("0x03", 10, Some(1)),
+            transaction("0x01", 10, None),
+            transaction("0x02", 10, Some(0)),
+            transaction("0x04", 11, Some(0)),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let mut after: Option<TransactionKey> = None;
+    let mut exported = vec![];
+
+    loop {
+        let chunk = Query::find_filtered_transactions_chunk(
+            &db,
+            CHAIN_ID,
+            ADDRESS,
+            &TransactionFilter::default(),
+            after.as_ref(),
+            2,
+        )
+        .await
+        .unwrap();
+
+        let Some(last) = chunk.last() else {
+            break;
+        };
+        after = Some(TransactionKey::from(&last.transaction));
+        exported.extend(
+            chunk
+                .into_iter()
+                .map(|transaction| transaction.transaction.tx_id),
+        );
+    }
+
+    // Missing transaction index sorts as zero, ties are broken by hash
+    assert_eq!(exported, ["0x01", "0x02", "0x03", "0x04"]);
+}
+
+#[tokio::test]
+async fn fee_details_and_coverage_are_stored() {
+    let db = migrated_db().await;
+
+    Mutation::save_transactions(
+        &db,
+        vec![
+            transaction("0x01", 100, Some(0)),
+            transaction("0x02", 200, Some(0)),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let mut without_fee_details =
+        Query::find_transactions_without_fee_details(&db, CHAIN_ID, ADDRESS, (0, 150), 10)
+            .await
+            .unwrap();
+    assert_eq!(without_fee_details.len(), 1);
+
+    without_fee_details[0].effective_gas_price = Some(U256::from(1));
+    without_fee_details[0].base_fee_per_gas = Some(U256::from(1));
+    Mutation::update_fee_details(&db, without_fee_details)
+        .await
+        .unwrap();
+
+    assert!(
+        Query::find_transactions_without_fee_details(&db, CHAIN_ID, ADDRESS, (0, 150), 10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let updated = Query::find_transaction_by_hash(&db, CHAIN_ID, "0x01")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.burnt_fee, Some(U256::from(21_000)));
+
+    Mutation::add_crawl_coverage(
+        &db,
+        CHAIN_ID,
+        ADDRESS,
+        CoverageSource::Transactions,
+        (0, 99),
+    )
+    .await
+    .unwrap();
+    Mutation::add_crawl_coverage(
+        &db,
+        CHAIN_ID,
+        ADDRESS,
+        CoverageSource::Transactions,
+        (100, MAX_STORED_NUMBER),
+    )
+    .await
+    .unwrap();
+    assert!(Query::find_uncovered_block_ranges(
+        &db,
+        CHAIN_ID,
+        ADDRESS,
+        CoverageSource::Transactions,
+        (0, MAX_STORED_NUMBER),
+    )
+    .await
+    .unwrap()
+    .is_empty());
+
+    assert_eq!(
+        Query::find_block_hashes_since_block(&db, CHAIN_ID, 150)
+            .await
+            .unwrap(),
+        [(200, "0xhash200".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn balance_overflow_is_reported() {
+    let db = migrated_db().await;
+
+    Mutation::save_transactions(&db, vec![transaction("0x01", 100, Some(0))])
+        .await
+        .unwrap();
+    let max_balance = U256::from_str(
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935",
+    )
+    .unwrap();
+
+    let balance = Query::balance_at(
+        &db,
+        CHAIN_ID,
+        ADDRESS,
+        BlockOrDate::Block(99),
+        100,
+        U256::zero(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(balance.sent_since, U256::from(1_000_000_000_000_000_000));
+
+    // Replaying sent value back onto the largest balance doesn't fit into 256 bits
+    assert!(Query::balance_at(
+        &db,
+        CHAIN_ID,
+        ADDRESS,
+        BlockOrDate::Block(99),
+        100,
+        max_balance
+    )
+    .await
+    .is_err());
+}
@@ -1,3 +1,5 @@
-fn main() {
-    actix_example_api::main();
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    actix_example_api::main()
 }